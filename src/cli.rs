@@ -0,0 +1,229 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::{
+  app_model::AppModel,
+  error::BotError,
+  input_backend::InputBackendKind
+};
+use std::str::FromStr;
+
+pub const USAGE: &str = "Usage: crowdplay-bot-rs [subcommand] [options]
+
+Subcommands:
+  init                Writes a commented config template, then exits.
+  check-config        Checks the config for problems without connecting.
+  replay <log>        Presses every input in an audit log again, and reports
+                      any that don't resolve to the same keys anymore.
+  console [script]    Reads chat from the console, or from a script file,
+                      instead of connecting to Discord.
+
+Options:
+  --config <path>     The config file to use. Defaults to \"./config.toml\".
+  --game <game>       Overrides the game, either \"ftl\" or \"nds\".
+  --prefix <prefix>   Overrides the command prefix.
+  --token-env <var>   Reads the token from an environment variable.
+  --dry-run           Prints key presses instead of sending them.
+  --speed <speed>     How fast to replay, like 2 for twice as fast. 0 replays
+                      without waiting between inputs. Defaults to 1.
+  --user <name>       Who the console speaks as. Defaults to \"console\".
+  --help              Shows this message.";
+
+#[derive(Copy, Clone)]
+pub enum Subcommand {
+  Run,
+  Init,
+  CheckConfig,
+  Replay,
+  Console,
+  Help
+}
+
+#[derive(Clone)]
+pub struct CliArgs {
+  subcommand: Subcommand,
+  config: String,
+  game: Option<SupportedGame>,
+  prefix: Option<String>,
+  token_env: Option<String>,
+  dry_run: bool,
+  replay_log: String,
+  speed: f64,
+  script: Option<String>,
+  user: String
+}
+
+// Flags like "--config" are useless without whatever comes after them.
+fn expect_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, BotError> {
+  match args.next() {
+    None => return Err(BotError::Usage(format!("{} expects a value.\n\n{}", flag, USAGE))),
+    Some(value) => return Ok(value)
+  }
+}
+
+impl CliArgs {
+  // Expects the arguments to include the program name, like env::args does.
+  pub fn parse(args: Vec<String>) -> Result<CliArgs, BotError> {
+    let mut cli: CliArgs = CliArgs {
+      subcommand: Subcommand::Run,
+      config: "./config.toml".to_string(),
+      game: None,
+      prefix: None,
+      token_env: None,
+      dry_run: false,
+      replay_log: String::new(),
+      speed: 1.0,
+      script: None,
+      user: "console".to_string()
+    };
+
+    let mut args = args.into_iter().skip(1).peekable();
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "init" => cli.subcommand = Subcommand::Init,
+        "check-config" => cli.subcommand = Subcommand::CheckConfig,
+        "replay" => {
+          cli.subcommand = Subcommand::Replay;
+          cli.replay_log = expect_value(&mut args, &arg)?;
+        },
+        "console" => {
+          cli.subcommand = Subcommand::Console;
+          // The script is optional, so anything that looks like a flag is
+          // left alone.
+          if args.peek().map_or(false, |next| !next.starts_with("-")) {
+            cli.script = args.next();
+          }
+        },
+        "-h" | "--help" => cli.subcommand = Subcommand::Help,
+        "--config" => cli.config = expect_value(&mut args, &arg)?,
+        "--game" => {
+          let game: String = expect_value(&mut args, &arg)?;
+          match SupportedGame::from_str(&game.to_lowercase()) {
+            Err(e) => return Err(BotError::Usage(e.to_string())),
+            Ok(game) => cli.game = Some(game)
+          }
+        },
+        "--prefix" => cli.prefix = Some(expect_value(&mut args, &arg)?),
+        "--token-env" => cli.token_env = Some(expect_value(&mut args, &arg)?),
+        "--dry-run" => cli.dry_run = true,
+        "--user" => cli.user = expect_value(&mut args, &arg)?,
+        "--speed" => {
+          let speed: String = expect_value(&mut args, &arg)?;
+          match speed.parse::<f64>() {
+            Ok(speed) if speed >= 0.0 && speed.is_finite() => cli.speed = speed,
+            _ => return Err(BotError::Usage(
+              format!("\"{}\" isn't a valid speed. Try something like 1 or 0.5.", speed)
+            ))
+          }
+        },
+        _ => return Err(BotError::Usage(
+          format!("\"{}\" isn't a valid argument.\n\n{}", arg, USAGE)
+        ))
+      }
+    }
+    return Ok(cli);
+  }
+
+  pub fn get_subcommand(&self) -> &Subcommand {
+    return &self.subcommand;
+  }
+
+  pub fn get_config(&self) -> &String {
+    return &self.config;
+  }
+
+  pub fn get_replay_log(&self) -> &String {
+    return &self.replay_log;
+  }
+
+  pub fn get_speed(&self) -> &f64 {
+    return &self.speed;
+  }
+
+  pub fn get_script(&self) -> &Option<String> {
+    return &self.script;
+  }
+
+  pub fn get_user(&self) -> &String {
+    return &self.user;
+  }
+
+  // Anything given on the command line wins over the config file.
+  pub fn apply(&self, cfg: Box<dyn AppModel>) -> Box<dyn AppModel> {
+    let mut cfg: Box<dyn AppModel> = cfg;
+    if let Some(game) = self.game {
+      cfg = cfg.set_game(Some(game));
+    }
+    if let Some(prefix) = &self.prefix {
+      cfg = cfg.set_prefix(prefix.to_string());
+    }
+    if let Some(var) = &self.token_env {
+      cfg = cfg.set_token_env(Some(var.to_string()));
+    }
+    if self.dry_run {
+      cfg = cfg.set_input_backend(InputBackendKind::DryRun);
+    }
+    return cfg;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str]) -> Result<CliArgs, BotError> {
+    let mut all: Vec<String> = vec!["crowdplay-bot-rs".to_string()];
+    all.extend(args.iter().map(|arg| arg.to_string()));
+    return CliArgs::parse(all);
+  }
+
+  #[test]
+  fn defaults_to_running_the_bot() {
+    let cli: CliArgs = parse(&[]).unwrap();
+    assert!(matches!(cli.get_subcommand(), Subcommand::Run));
+    assert_eq!(cli.get_config(), "./config.toml");
+    assert_eq!(*cli.get_speed(), 1.0);
+    assert_eq!(cli.get_user(), "console");
+  }
+
+  #[test]
+  fn parses_overrides() {
+    let cli: CliArgs = parse(&["init", "--config", "other.toml", "--game", "FTL", "--prefix", "?", "--dry-run"]).unwrap();
+    assert!(matches!(cli.get_subcommand(), Subcommand::Init));
+    assert_eq!(cli.get_config(), "other.toml");
+    assert!(matches!(cli.game, Some(SupportedGame::FTL)));
+    assert_eq!(cli.prefix, Some("?".to_string()));
+    assert!(cli.dry_run);
+  }
+
+  #[test]
+  fn parses_subcommands_and_flags() {
+    let cli: CliArgs = parse(&["replay", "audit.jsonl", "--speed", "2", "--config", "other.toml", "--dry-run"]).unwrap();
+    assert!(matches!(cli.get_subcommand(), Subcommand::Replay));
+    assert_eq!(cli.get_replay_log(), "audit.jsonl");
+    assert_eq!(*cli.get_speed(), 2.0);
+    assert_eq!(cli.get_config(), "other.toml");
+    assert!(cli.dry_run);
+  }
+
+  #[test]
+  fn console_script_is_optional() {
+    let cli: CliArgs = parse(&["console", "chat.txt", "--user", "alice"]).unwrap();
+    assert!(matches!(cli.get_subcommand(), Subcommand::Console));
+    assert_eq!(cli.get_script(), &Some("chat.txt".to_string()));
+    assert_eq!(cli.get_user(), "alice");
+
+    let cli: CliArgs = parse(&["console", "--dry-run"]).unwrap();
+    assert!(matches!(cli.get_subcommand(), Subcommand::Console));
+    assert_eq!(cli.get_script(), &None);
+    assert!(cli.dry_run);
+  }
+
+  #[test]
+  fn rejects_bad_arguments() {
+    assert!(matches!(parse(&["--config"]), Err(BotError::Usage(_))));
+    assert!(matches!(parse(&["replay"]), Err(BotError::Usage(_))));
+    assert!(matches!(parse(&["--bogus"]), Err(BotError::Usage(_))));
+    assert!(matches!(parse(&["--game", "tetris"]), Err(BotError::Usage(_))));
+    assert!(matches!(parse(&["--speed", "-1"]), Err(BotError::Usage(_))));
+    assert!(matches!(parse(&["--speed", "NaN"]), Err(BotError::Usage(_))));
+  }
+}
\ No newline at end of file
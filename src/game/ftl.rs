@@ -1,9 +1,11 @@
-use crate::model::bot::{MessageParser, GameFocusChecker};
+use crate::model::bot::MessageParser;
+use crate::model::error::{InputError, ParseError};
+use crate::model::input_backend::InputBackend;
 use crate::model::key::{KeyInputtable, KeyMappable, KeyInput};
-use crate::model::win_utils::{get_focused_window};
-use std::str::{FromStr, Split};
+use std::str::{FromStr, SplitWhitespace};
 use inputbot::{KeybdKey, KeybdKey::*};
 
+#[derive(Copy, Clone)]
 pub enum FTLTarget {
   Event,
   Shields,
@@ -22,27 +24,61 @@ pub enum FTLTarget {
   BackupBattery
 }
 
+const TARGET_ALIASES: &[(&str, FTLTarget)] = &[
+  ("event", FTLTarget::Event),
+  ("choice", FTLTarget::Event),
+  ("choose", FTLTarget::Event),
+  ("shields", FTLTarget::Shields),
+  ("shield", FTLTarget::Shields),
+  ("s", FTLTarget::Shields),
+  ("engines", FTLTarget::Engines),
+  ("engine", FTLTarget::Engines),
+  ("e", FTLTarget::Engines),
+  ("oxygen", FTLTarget::Oxygen),
+  ("o2", FTLTarget::Oxygen),
+  ("o", FTLTarget::Oxygen),
+  ("medbay", FTLTarget::Medbay),
+  ("med", FTLTarget::Medbay),
+  ("mb", FTLTarget::Medbay),
+  ("clone_bay", FTLTarget::CloneBay),
+  ("clone", FTLTarget::CloneBay),
+  ("cb", FTLTarget::CloneBay),
+  ("teleporter", FTLTarget::Teleporter),
+  ("teleport", FTLTarget::Teleporter),
+  ("tp", FTLTarget::Teleporter),
+  ("cloaking", FTLTarget::Cloaking),
+  ("cloak", FTLTarget::Cloaking),
+  ("c", FTLTarget::Cloaking),
+  ("mind_control", FTLTarget::MindControl),
+  ("mind", FTLTarget::MindControl),
+  ("mc", FTLTarget::MindControl),
+  ("hacking", FTLTarget::Hacking),
+  ("hack", FTLTarget::Hacking),
+  ("h", FTLTarget::Hacking),
+  ("artillery", FTLTarget::ArtilleryBeam),
+  ("beam", FTLTarget::ArtilleryBeam),
+  ("a", FTLTarget::ArtilleryBeam),
+  ("weapons", FTLTarget::WeaponControl),
+  ("weapon", FTLTarget::WeaponControl),
+  ("wep", FTLTarget::WeaponControl),
+  ("w", FTLTarget::WeaponControl),
+  ("drones", FTLTarget::DroneControl),
+  ("drone", FTLTarget::DroneControl),
+  ("d", FTLTarget::DroneControl),
+  ("doors", FTLTarget::Doors),
+  ("door", FTLTarget::Doors),
+  ("backup", FTLTarget::BackupBattery),
+  ("battery", FTLTarget::BackupBattery),
+  ("b", FTLTarget::BackupBattery)
+];
+
 impl FromStr for FTLTarget {
-  type Err = ();
+  type Err = ParseError;
 
   fn from_str(s: &str) -> Result<FTLTarget, Self::Err> {
-    match s {
-      "event" | "choice" | "choose" => return Ok(FTLTarget::Event),
-      "shields" | "shield" | "s" => return Ok(FTLTarget::Shields),
-      "engines" | "engine" | "e" => return Ok(FTLTarget::Engines),
-      "oxygen" | "o2" | "o" => return Ok(FTLTarget::Oxygen),
-      "medbay" | "med" | "mb" => return Ok(FTLTarget::Medbay),
-      "clone_bay" | "clone" | "cb" => return Ok(FTLTarget::CloneBay),
-      "teleporter" | "teleport" | "tp" => return Ok(FTLTarget::Teleporter),
-      "cloaking" | "cloak" | "c" => return Ok(FTLTarget::Cloaking),
-      "mind_control" | "mind" | "mc" => return Ok(FTLTarget::MindControl),
-      "hacking" | "hack" | "h" => return Ok(FTLTarget::Hacking),
-      "artillery" | "beam" | "a" => return Ok(FTLTarget::ArtilleryBeam),
-      "weapons" | "weapon" | "wep" | "w" => return Ok(FTLTarget::WeaponControl),
-      "drones" | "drone" | "d" => return Ok(FTLTarget::DroneControl),
-      "doors" | "door" => return Ok(FTLTarget::Doors),
-      "backup" | "battery" | "b" => return Ok(FTLTarget::BackupBattery),
-      _ => return Err(())
+    match TARGET_ALIASES.iter().find(|(alias, _)| *alias == s) {
+      None => return Err(ParseError::UnknownCommand(s.to_string())),
+      Some((_, target)) => return Ok(*target)
     }
   }
 }
@@ -107,13 +143,17 @@ impl KeyMappable for FTLInput {
 
 impl KeyInputtable for FTLInput {
 
-  fn pop(&self) -> Option<Box<dyn KeyInputtable + Send + Sync>> {
-    return self.to_key_input().pop();
+  fn pop(&self, backend: &dyn InputBackend) -> Result<Option<Box<dyn KeyInputtable + Send + Sync>>, InputError> {
+    return self.to_key_input().pop(backend);
   }
 
   fn get_presses(&self) -> &i8 {
     return &self.presses;
   }
+
+  fn get_keys(&self) -> Vec<KeybdKey> {
+    return self.to_key_input().get_keys();
+  }
 }
 
 pub struct FTLUtils;
@@ -149,7 +189,7 @@ impl FTLUtils {
     }
   }
 
-  fn parse_action(&self, target: FTLTarget, arg: Option<&str>) -> Result<FTLAction, &str> {
+  fn parse_action(&self, target: FTLTarget, arg: Option<&str>) -> Result<FTLAction, ParseError> {
     match target {
       FTLTarget::Shields => {
         return self.parse_system_arg(FTLAction::PowerShields, arg);
@@ -199,16 +239,18 @@ impl FTLUtils {
     }
   }
 
-  fn parse_system_arg(&self, action: FTLAction, arg: Option<&str>) -> Result<FTLAction, &str> {
-    let power_err: Result<FTLAction, &str> =
-      Err("System power allocation must be accompanied by an integer whose
-      absolute value is in the range [1, 8].");
+  fn parse_system_arg(&self, action: FTLAction, arg: Option<&str>) -> Result<FTLAction, ParseError> {
+    let power_err: Result<FTLAction, ParseError> =
+      Err(ParseError::InvalidArgument(
+        "System power allocation must be accompanied by an integer whose absolute value is in the range [1, 8].".to_string()
+      ));
     match arg {
       None => return power_err,
       Some(power_arg) => match power_arg.parse::<i8>() {
         Err(_) => power_err,
         Ok(i) => {
-          let a: i8 = i8::abs(i);
+          // i8::abs would overflow on -128, so this has to be unsigned.
+          let a: u8 = i.unsigned_abs();
           if a >= 1 && a <= 8 {
             return Ok(action);
           } else {
@@ -219,16 +261,18 @@ impl FTLUtils {
     }
   }
 
-  fn parse_startable_system_arg(&self, start_action: FTLAction, power_action: FTLAction, arg: Option<&str>) -> Result<FTLAction, &str> {
-    let power_err: Result<FTLAction, &str> =
-      Err("System power allocation must be accompanied by an integer whose
-      absolute value is in the range [1, 8].");
+  fn parse_startable_system_arg(&self, start_action: FTLAction, power_action: FTLAction, arg: Option<&str>) -> Result<FTLAction, ParseError> {
+    let power_err: Result<FTLAction, ParseError> =
+      Err(ParseError::InvalidArgument(
+        "System power allocation must be accompanied by an integer whose absolute value is in the range [1, 8].".to_string()
+      ));
     match arg {
       None => return Ok(start_action),
       Some(power_arg) => match power_arg.parse::<i8>() {
         Err(_) => power_err,
         Ok(i) => {
-          let a: i8 = i8::abs(i);
+          // i8::abs would overflow on -128, so this has to be unsigned.
+          let a: u8 = i.unsigned_abs();
           if a >= 1 && a <= 8 {
             return Ok(power_action);
           } else {
@@ -239,10 +283,11 @@ impl FTLUtils {
     }
   }
 
-  fn parse_event_arg(&self, arg: Option<&str>) -> Result<FTLAction, &str> {
-    let event_err: Result<FTLAction, &str> =
-      Err("Event choice must be accompanied by an integer within the range 
-      [1, 4].");
+  fn parse_event_arg(&self, arg: Option<&str>) -> Result<FTLAction, ParseError> {
+    let event_err: Result<FTLAction, ParseError> =
+      Err(ParseError::InvalidArgument(
+        "Event choice must be accompanied by an integer within the range [1, 4].".to_string()
+      ));
     match arg {
       None => return event_err,
       Some(choice_arg) => match choice_arg.parse::<i8>() {
@@ -258,10 +303,11 @@ impl FTLUtils {
     } 
   }
 
-  fn parse_weapon_arg(&self, arg: Option<&str>) -> Result<FTLAction, &str> {
-    let weapon_err: Result<FTLAction, &str> =
-      Err("Weapon control must be accompanied by an integer within the range
-      [1, 4].");
+  fn parse_weapon_arg(&self, arg: Option<&str>) -> Result<FTLAction, ParseError> {
+    let weapon_err: Result<FTLAction, ParseError> =
+      Err(ParseError::InvalidArgument(
+        "Weapon control must be accompanied by an integer within the range [1, 4].".to_string()
+      ));
     match arg {
       None => return weapon_err,
       Some(choice_arg) => match choice_arg.parse::<i8>() {
@@ -277,10 +323,11 @@ impl FTLUtils {
     }
   }
 
-  fn parse_drone_arg(&self, arg: Option<&str>) -> Result<FTLAction, &str> {
-    let drone_err: Result<FTLAction, &str> =
-      Err("Drone control must be accompanied by an integer within the range
-      [1, 3].");
+  fn parse_drone_arg(&self, arg: Option<&str>) -> Result<FTLAction, ParseError> {
+    let drone_err: Result<FTLAction, ParseError> =
+      Err(ParseError::InvalidArgument(
+        "Drone control must be accompanied by an integer within the range [1, 3].".to_string()
+      ));
     match arg {
       None => return drone_err,
       Some(choice_arg) => match choice_arg.parse::<i8>() {
@@ -295,9 +342,11 @@ impl FTLUtils {
     }
   }
 
-  fn parse_doors_arg(&self, arg: Option<&str>) -> Result<FTLAction, &str> {
-    let doors_err: Result<FTLAction, &str> =
-      Err("Door control must be accompanied by either \"open\" or \"close\".");
+  fn parse_doors_arg(&self, arg: Option<&str>) -> Result<FTLAction, ParseError> {
+    let doors_err: Result<FTLAction, ParseError> =
+      Err(ParseError::InvalidArgument(
+        "Door control must be accompanied by either \"open\" or \"close\".".to_string()
+      ));
     match arg {
       None => return doors_err,
       Some(doors_arg) => match doors_arg {
@@ -321,17 +370,17 @@ impl FTLUtils {
 }
 
 impl MessageParser for FTLUtils {
-  fn parse_msg(&self, content: &str) -> Result<Box<dyn KeyInputtable + Send + Sync>, ()> {
-    let mut split: Split<&str> = content.split(" ");
+  fn parse_msg(&self, content: &str) -> Result<Box<dyn KeyInputtable + Send + Sync>, ParseError> {
+    let mut split: SplitWhitespace = content.split_whitespace();
     match split.next() {
-      None => return Err(()),
+      None => return Err(ParseError::Empty),
       Some(cmd) => match FTLTarget::from_str(cmd) {
-        Err(_) => return Err(()),
+        Err(e) => return Err(e),
         Ok(target) => {
           let arg: Option<&str> = split.next();
-          let parsed: Result<FTLAction, &str> = self.parse_action(target, arg);
+          let parsed: Result<FTLAction, ParseError> = self.parse_action(target, arg);
           match parsed {
-            Err(_) => return Err(()),
+            Err(e) => return Err(e),
             Ok(action) if self.is_power_action(&action) => {
               return Ok(Box::new(
                 FTLInput {
@@ -353,13 +402,8 @@ impl MessageParser for FTLUtils {
       }
     }
   }
-}
 
-impl GameFocusChecker for FTLUtils {
-  fn game_focused(&self) -> bool {
-    match get_focused_window().to_str() {
-      None => return false,
-      Some(title) => return title == "FTL: Faster Than Light"
-    }
+  fn aliases(&self) -> Vec<&'static str> {
+    return TARGET_ALIASES.iter().map(|(alias, _)| *alias).collect();
   }
 }
\ No newline at end of file
@@ -1,10 +1,11 @@
-use crate::model::bot::{MessageParser, GameFocusChecker};
+use crate::model::bot::MessageParser;
+use crate::model::error::{InputError, ParseError};
+use crate::model::input_backend::InputBackend;
 use crate::model::key::{KeyInputtable, KeyMappable, KeyInput};
-use crate::model::win_utils::{get_focused_window};
-use std::str::FromStr;
+use std::{convert::TryFrom, str::FromStr};
 use inputbot::{KeybdKey, KeybdKey::*};
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub enum NDSAction {
   Up,
   Down,
@@ -20,24 +21,28 @@ pub enum NDSAction {
   Select
 }
 
+const ACTION_ALIASES: &[(&str, NDSAction)] = &[
+  ("up", NDSAction::Up),
+  ("down", NDSAction::Down),
+  ("left", NDSAction::Left),
+  ("right", NDSAction::Right),
+  ("y", NDSAction::Y),
+  ("x", NDSAction::X),
+  ("a", NDSAction::A),
+  ("b", NDSAction::B),
+  ("l", NDSAction::L),
+  ("r", NDSAction::R),
+  ("start", NDSAction::Start),
+  ("select", NDSAction::Select)
+];
+
 impl FromStr for NDSAction {
-  type Err = ();
+  type Err = ParseError;
 
   fn from_str(s: &str) -> Result<NDSAction, Self::Err> {
-    match s {
-      "up" => Ok(NDSAction::Up),
-      "down" => Ok(NDSAction::Down),
-      "left" => Ok(NDSAction::Left),
-      "right" => Ok(NDSAction::Right),
-      "y" => Ok(NDSAction::Y),
-      "x" => Ok(NDSAction::X),
-      "a" => Ok(NDSAction::A),
-      "b" => Ok(NDSAction::B),
-      "l" => Ok(NDSAction::L),
-      "r" => Ok(NDSAction::R),
-      "start" => Ok(NDSAction::Start),
-      "select" => Ok(NDSAction::Select),
-      _ => Err(())
+    match ACTION_ALIASES.iter().find(|(alias, _)| *alias == s) {
+      None => return Err(ParseError::UnknownCommand(s.to_string())),
+      Some((_, action)) => return Ok(*action)
     }
   }
 }
@@ -67,13 +72,17 @@ impl KeyMappable for NDSInput {
 
 impl KeyInputtable for NDSInput {
 
-  fn pop(&self) -> Option<Box<dyn KeyInputtable + Send + Sync>> {
-    return self.to_key_input().pop();
+  fn pop(&self, backend: &dyn InputBackend) -> Result<Option<Box<dyn KeyInputtable + Send + Sync>>, InputError> {
+    return self.to_key_input().pop(backend);
   }
 
   fn get_presses(&self) -> &i8 {
     return &self.presses;
   }
+
+  fn get_keys(&self) -> Vec<KeybdKey> {
+    return self.to_key_input().get_keys();
+  }
 }
 
 pub struct NDSUtils;
@@ -98,23 +107,29 @@ impl NDSUtils {
 }
 
 impl MessageParser for NDSUtils {
-  fn parse_msg(&self, content: &str) -> Result<Box<dyn KeyInputtable + Send + Sync>, ()> {
-    let mut split = content.split(" ");
+  fn parse_msg(&self, content: &str) -> Result<Box<dyn KeyInputtable + Send + Sync>, ParseError> {
+    let mut split = content.split_whitespace();
     match split.next() {
-      None => return Err(()),
+      None => return Err(ParseError::Empty),
       Some(cmd) => {
         match NDSAction::from_str(cmd) {
-          Err(_) => return Err(()),
+          Err(e) => return Err(e),
           Ok(action) => {
             match split.next() {
               None => return Ok(Box::new(
                 NDSInput { action: action, presses: 1 })),
               Some(arg) => {
-                match arg.parse::<i8>() {
+                // Counts that don't fit in an input are still counts, so they
+                // get rejected by the repeat policy instead of quietly turning
+                // into a single press.
+                match arg.parse::<i64>() {
                   Err(_) => return Ok(Box::new(
                     NDSInput { action: action, presses: 1 })),
-                  Ok(int) => return Ok(Box::new(
-                    NDSInput { action: action, presses: int }))
+                  Ok(int) => match i8::try_from(int) {
+                    Err(_) => return Err(ParseError::RepeatCount(int)),
+                    Ok(int) => return Ok(Box::new(
+                      NDSInput { action: action, presses: int }))
+                  }
                 }
               }
             }
@@ -123,13 +138,8 @@ impl MessageParser for NDSUtils {
       }
     }
   }
-}
 
-impl GameFocusChecker for NDSUtils {
-  fn game_focused(&self) -> bool {
-    match get_focused_window().to_str() {
-      None => return false,
-      Some(title) => return title.contains("DeSmuME")
-    }
+  fn aliases(&self) -> Vec<&'static str> {
+    return ACTION_ALIASES.iter().map(|(alias, _)| *alias).collect();
   }
 }
\ No newline at end of file
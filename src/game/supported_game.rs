@@ -1,20 +1,32 @@
+use crate::model::error::ParseError;
 use serde::{Serialize, Deserialize};
 use std::str::FromStr;
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SupportedGame {
   FTL,
   NDS
 }
 
+impl SupportedGame {
+  // The short name that FromStr understands, for writing the game somewhere
+  // it'll be read back from.
+  pub fn to_id(&self) -> &'static str {
+    match self {
+      SupportedGame::FTL => "ftl",
+      SupportedGame::NDS => "nds"
+    }
+  }
+}
+
 impl FromStr for SupportedGame {
-  type Err = ();
+  type Err = ParseError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
     match s {
       "ftl" => Ok(SupportedGame::FTL),
       "nds" => Ok(SupportedGame::NDS),
-      _ => Err(())
+      _ => Err(ParseError::UnknownGame(s.to_string()))
     }
   }
 }
@@ -0,0 +1,3 @@
+pub mod cli;
+pub mod model;
+pub mod game;
\ No newline at end of file
@@ -1,23 +1,94 @@
-pub mod model;
-pub mod game;
+use crowdplay_bot_rs::cli::{CliArgs, Subcommand, USAGE};
+use crowdplay_bot_rs::model::{
+  app_model::{AppModel, AppModelSerenity, write_config_template},
+  audit::{AuditEntry, read_audit_log, replay},
+  bot::Bot,
+  config_watcher::{ConfigLoader, ConfigWatcher},
+  error::{BotError, ConfigError, PlatformError}
+};
+use std::{env, process};
 
-use crate::model::app_model::{AppModel, AppModelSerenity};
-use confy::ConfyError;
+fn load_config(path: &str) -> Result<Box<dyn AppModel>, ConfigError> {
+  match confy::load_path::<AppModelSerenity>(path) {
+    Err(e) => return Err(ConfigError::Load(path.to_string(), e)),
+    Ok(cfg) => return Ok(Box::new(cfg))
+  }
+}
+
+// Reloads go through the same overrides as the first load, so that whatever was
+// given on the command line sticks around.
+fn watch_config(cli: &CliArgs, connects: bool) -> ConfigWatcher {
+  let reload_cli: CliArgs = cli.clone();
+  let loader: ConfigLoader = Box::new(move || {
+    return Ok(reload_cli.apply(load_config(reload_cli.get_config())?));
+  });
+  return ConfigWatcher::new(cli.get_config().to_string(), loader, connects);
+}
 
-fn load_config() -> Result<Box<dyn AppModel>, ConfyError> {
-  let cfg: AppModelSerenity = confy::load_path("./config.toml")?;
-  return Ok(Box::new(cfg));
+// Runs the bot until it stops on its own or Ctrl+C is pressed, which is the only
+// way Discord and IRC bots ever stop, then makes sure nothing's left unwritten.
+async fn run_bot(mut bot: Box<dyn Bot + Send + Sync>) -> Result<(), BotError> {
+  let result: Result<(), PlatformError> = tokio::select! {
+    result = bot.start() => result,
+    Ok(()) = tokio::signal::ctrl_c() => Ok(())
+  };
+  bot.flush().await;
+  return Ok(result?);
+}
+
+async fn run() -> Result<(), BotError> {
+  let cli: CliArgs = CliArgs::parse(env::args().collect())?;
+  match cli.get_subcommand() {
+    Subcommand::Help => {
+      println!("{}", USAGE);
+      return Ok(());
+    },
+    Subcommand::Init => {
+      write_config_template(cli.get_config())?;
+      println!("Wrote a config template to \"{}\".", cli.get_config());
+      return Ok(());
+    },
+    Subcommand::CheckConfig => {
+      let cfg: Box<dyn AppModel> = cli.apply(load_config(cli.get_config())?);
+      cfg.validate()?;
+      println!("\"{}\" looks good.", cli.get_config());
+      return Ok(());
+    },
+    Subcommand::Replay => {
+      let cfg: Box<dyn AppModel> = cli.apply(load_config(cli.get_config())?);
+      let entries: Vec<AuditEntry> = read_audit_log(cli.get_replay_log())?;
+      replay(&cfg, &entries, *cli.get_speed())?;
+      println!("Replayed {} input(s) from \"{}\".", entries.len(), cli.get_replay_log());
+      return Ok(());
+    },
+    Subcommand::Run => {
+      let cfg: Box<dyn AppModel> = cli.apply(load_config(cli.get_config())?);
+      // This can only fail if there's already a logger, which there won't be.
+      let _ = tracing::subscriber::set_global_default(cfg.build_logger()?);
+
+      // The next step aside from the config is probably to start the client in
+      // another thread. I'm not looking forward to that though.
+      return run_bot(cfg.build_bot(Some(watch_config(&cli, true))).await?).await;
+    },
+    Subcommand::Console => {
+      let cfg: Box<dyn AppModel> = cli.apply(load_config(cli.get_config())?);
+      let _ = tracing::subscriber::set_global_default(cfg.build_logger()?);
+      let bot: Box<dyn Bot + Send + Sync> = cfg.build_console(
+        Some(watch_config(&cli, false)),
+        cli.get_script().clone(),
+        cli.get_user().to_string()
+      ).await?;
+      return run_bot(bot).await;
+    }
+  }
 }
 
 #[tokio::main]
-async fn main() -> Result<(), String> {
-  let cfg: Box<dyn AppModel> = load_config()
-    .expect("Expected a model to be generated from a config.");
- 
-  // The next step aside from the config is probably to start the client in
-  // another thread. I'm not looking forward to that though.
-  match cfg.start().await {
-    Err(e) => return Err(e),
-    Ok(_) => return Ok(())
+async fn main() -> () {
+  // Errors are printed with Display rather than Debug, since they're meant to
+  // tell whoever's running the bot what to fix.
+  if let Err(e) = run().await {
+    eprintln!("{}", e);
+    process::exit(1);
   }
 }
\ No newline at end of file
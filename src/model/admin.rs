@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+// Commands that only streamers (or whoever they trust) should be able to use.
+// These are checked before anything gets handed off to the game's parser, so
+// none of these names can double as game inputs.
+pub enum AdminCommand {
+  Pause,
+  Resume,
+  Clear,
+  Skip,
+  Ban,
+  Unban,
+  Game
+}
+
+impl FromStr for AdminCommand {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<AdminCommand, Self::Err> {
+    match s {
+      "pause" => return Ok(AdminCommand::Pause),
+      "resume" => return Ok(AdminCommand::Resume),
+      "clear" => return Ok(AdminCommand::Clear),
+      "skip" => return Ok(AdminCommand::Skip),
+      "ban" => return Ok(AdminCommand::Ban),
+      "unban" => return Ok(AdminCommand::Unban),
+      "game" => return Ok(AdminCommand::Game),
+      _ => return Err(())
+    }
+  }
+}
+
+// Accepts either a raw user ID or a mention, which Discord sends as "<@id>" or
+// "<@!id>" depending on whether the user has a nickname.
+pub fn parse_user_mention(s: &str) -> Option<u64> {
+  let id: &str = s.trim_start_matches("<@")
+    .trim_start_matches("!")
+    .trim_end_matches(">");
+  match id.parse::<u64>() {
+    Err(_) => return None,
+    Ok(id) => return Some(id)
+  }
+}
+
+// Everyone that's allowed to use admin commands, identified either by one of
+// their roles or by their own user ID.
+pub struct AdminList {
+  roles: Vec<u64>,
+  users: Vec<u64>
+}
+
+impl AdminList {
+  pub fn new(roles: Vec<u64>, users: Vec<u64>) -> AdminList {
+    return AdminList {
+      roles: roles,
+      users: users
+    }
+  }
+
+  pub fn is_admin(&self, user_id: u64, role_ids: &Vec<u64>) -> bool {
+    return self.users.contains(&user_id)
+      || role_ids.iter().any(|role_id| self.roles.contains(role_id));
+  }
+}
\ No newline at end of file
@@ -1,64 +1,352 @@
-use crate::model::bot::{Bot, BotSerenity, GameFocusChecker, MessageParser};
+use crate::model::{
+  admin::AdminList,
+  audit::AuditLog,
+  ban_list::BanList,
+  bot::{Bot, BotSerenity, GameFocusChecker, MessageParser, Platform},
+  config_watcher::ConfigWatcher,
+  console::BotConsole,
+  dispatcher::Dispatcher,
+  error::{BotError, ConfigError, ConfigIssue},
+  focus_loss::{FocusLoss, FocusLossPolicy},
+  focus_rule::{FocusRules, RuleFocusChecker, WindowMatcher},
+  http,
+  input_backend::InputBackendKind,
+  irc::{BotIrc, IrcConfig},
+  logging::{LogLevel, Logger, build_logger},
+  overlay::Overlay,
+  prefix::PrefixMatcher,
+  repeat_policy::RepeatLimits,
+  secret::Secret,
+  settings::BotSettings,
+  user_stats::StatsList,
+  scope::{CommandScope, DmPolicy},
+  viewer::QueueFeedback
+};
 use crate::game::{
   ftl::FTLUtils,
   nds::NDSUtils,
   supported_game::{SupportedGame}
 };
+use crate::model::repeat_policy::RepeatPolicy;
+use hyper::server::{Builder, conn::AddrIncoming};
+use regex::Regex;
 use serde::{Serialize, Deserialize};
 use serenity::async_trait;
+use std::{env, fs::{self, File, OpenOptions}, net::SocketAddr, path::Path, sync::Arc};
+
+// confy can't write comments, so this is what gets written when someone asks
+// for a fresh config. Keep it in sync with AppModelSerenity's defaults.
+const CONFIG_TEMPLATE: &str = include_str!("config_template.toml");
+
+pub fn write_config_template(path: &str) -> Result<(), ConfigError> {
+  if Path::new(path).exists() {
+    return Err(ConfigError::AlreadyExists(path.to_string()));
+  }
+  match fs::write(path, CONFIG_TEMPLATE) {
+    Err(e) => return Err(ConfigError::Write(path.to_string(), e)),
+    Ok(_) => return Ok(())
+  }
+}
 
+// Running bots keep a copy of the config they were built from, so it has to be
+// safe to share between threads.
 #[async_trait]
-pub trait AppModel {
-  async fn start(&self) -> Result<(), String>;
+pub trait AppModel: Send + Sync {
+  // The watcher is optional, and lets the bot pick up changes to the config
+  // without restarting.
+  async fn build_bot(&self, watcher: Option<ConfigWatcher>) -> Result<Box<dyn Bot + Send + Sync>, BotError>;
 
-  async fn build_bot(&self) -> Result<Box<dyn Bot + Send + Sync>, &str>;
+  // Builds a bot that reads chat from the console instead of Discord, as
+  // whoever's named by `user`, either from stdin or from a script file.
+  async fn build_console(&self, watcher: Option<ConfigWatcher>, script: Option<String>, user: String)
+    -> Result<Box<dyn Bot + Send + Sync>, BotError>;
+
+  // Builds everything that can be swapped out while the bot is running, which
+  // is pretty much everything except the token and ban list. The audit log is
+  // opened once by whoever starts the bot and shared from then on.
+  fn build_settings(&self, audit_log: Option<Arc<AuditLog>>) -> Result<BotSettings, ConfigError>;
+
+  // Checks everything that can be checked without connecting, and reports all
+  // of the problems at once rather than stopping at the first.
+  fn validate(&self) -> Result<(), ConfigError>;
+
+  // Everything validate checks, except that the connection settings can be
+  // skipped for frontends that don't connect to anything.
+  fn check(&self, connects: bool) -> Result<(), ConfigError>;
+
+  // Logging is set up once at startup, so unlike most settings, changes to it
+  // need a restart.
+  fn build_logger(&self) -> Result<Logger, ConfigError>;
 
   fn get_prefix(&self) -> &String;
 
   fn set_prefix(&self, prefix: String) -> Box<dyn AppModel>;
 
-  fn get_token(&self) -> &String;
+  fn get_token(&self) -> &Option<Secret>;
+
+  fn set_token(&self, token: Option<Secret>) -> Box<dyn AppModel>;
 
-  fn set_token(&self, token: String) -> Box<dyn AppModel>;
+  fn get_token_env(&self) -> &Option<String>;
+
+  fn set_token_env(&self, token_env: Option<String>) -> Box<dyn AppModel>;
 
   fn get_game(&self) -> &Option<SupportedGame>;
 
   fn set_game(&self, game: Option<SupportedGame>) -> Box<dyn AppModel>;
+
+  fn get_input_backend(&self) -> &InputBackendKind;
+
+  fn set_input_backend(&self, input_backend: InputBackendKind) -> Box<dyn AppModel>;
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppModelSerenity {
+  // Which chat to connect to. IRC is set up in the `irc` table, and ignores
+  // the token and everything else that's made of Discord IDs.
+  platform: Platform,
   prefix: String,
-  token: String,
-  game: Option<SupportedGame>
+  // A regular expression that's also accepted as a prefix, like "^(cp!|!!)".
+  prefix_pattern: Option<String>,
+  // Whether "@bot up" works the same as a prefixed "up".
+  mention_prefix: bool,
+  // Channels where every message is a command, so no prefix is needed.
+  prefixless_channels: Vec<u64>,
+  // The token can come from one of three places. In order of precedence, an
+  // environment variable, a file containing nothing but the token, or this
+  // file itself, which is the worst place to keep it.
+  token_env: Option<String>,
+  token_file: Option<String>,
+  token: Option<Secret>,
+  game: Option<SupportedGame>,
+  // Discord role and user IDs that are allowed to use admin commands.
+  admin_roles: Vec<u64>,
+  admin_users: Vec<u64>,
+  // Guild and channel IDs that commands are read from. Leaving either empty
+  // allows all of them.
+  allowed_guilds: Vec<u64>,
+  allowed_channels: Vec<u64>,
+  dm_policy: DmPolicy,
+  // Where banned users are kept track of between runs.
+  ban_file: String,
+  // Where everyone's input counts, favorite actions and streaks are kept.
+  stats_file: String,
+  // How key presses get to the game.
+  input_backend: InputBackendKind,
+  // What happens to queued inputs while the game doesn't have focus, and how
+  // many seconds DropAfter waits before dropping them.
+  focus_loss_policy: FocusLossPolicy,
+  focus_loss_timeout: u64,
+  // The channel ID that status updates are announced in.
+  status_channel: Option<u64>,
+  // How much gets logged, both from the bot and from the libraries it uses.
+  log_level: LogLevel,
+  library_log_level: LogLevel,
+  // A file that logs are also written to, one JSON object per line.
+  log_file: Option<String>,
+  // How users find out where their input landed in the queue.
+  queue_feedback: QueueFeedback,
+  // How many inputs can be queued at once. Past this, the oldest input that
+  // hasn't started yet is dropped to make room. 0 means there's no limit.
+  max_queue: usize,
+  // A file that every executed input is added to, for settling disputes or
+  // replaying later.
+  audit_file: Option<String>,
+  // An address like "127.0.0.1:9100" to serve Prometheus metrics on, at
+  // /metrics.
+  metrics_address: Option<String>,
+  // An address like "127.0.0.1:9200" to serve the stream overlay on, and how
+  // many executed inputs it shows.
+  overlay_address: Option<String>,
+  overlay_history: usize,
+  // Everything from here on is written as a table, and TOML can't have plain
+  // values after tables, so new plain values have to go above.
+  // How many times a single input can be repeated, per game.
+  repeat_limits: RepeatLimits,
+  // How to tell whether each game has focus, since inputs are held until it
+  // does.
+  focus_rules: FocusRules,
+  irc: IrcConfig
 }
 
 impl AppModelSerenity {
-  pub fn new(prefix: String, token: String, game: Option<SupportedGame>) -> AppModelSerenity {
+  pub fn new(prefix: String, token: Option<Secret>, game: Option<SupportedGame>) -> AppModelSerenity {
     return AppModelSerenity {
       prefix: prefix,
       token: token,
-      game: game
+      game: game,
+      ..AppModelSerenity::default()
+    }
+  }
+
+  fn resolve_token(&self) -> Result<Secret, ConfigError> {
+    let token: Secret = match (&self.token_env, &self.token_file, &self.token) {
+      (Some(var), _, _) => match env::var(var) {
+        Err(_) => return Err(ConfigError::MissingTokenEnv(var.to_string())),
+        Ok(token) => Secret::new(token)
+      },
+      (None, Some(path), _) => match fs::read_to_string(path) {
+        Err(e) => return Err(ConfigError::TokenFile(path.to_string(), e)),
+        Ok(token) => Secret::new(token.trim().to_string())
+      },
+      (None, None, Some(token)) => token.clone(),
+      (None, None, None) => return Err(ConfigError::MissingToken)
+    };
+    if token.is_empty() {
+      return Err(ConfigError::MissingToken);
     }
+    return Ok(token);
+  }
+
+  fn validate_prefix(&self, issues: &mut Vec<ConfigIssue>) -> () {
+    if self.prefix.is_empty() && self.prefix_pattern.is_none()
+      && !self.mention_prefix && self.prefixless_channels.is_empty() {
+      issues.push(ConfigIssue::new(
+        "prefix",
+        "The prefix is empty and there's nothing else to mark commands, so no messages would be read.",
+        "Set a prefix like \";\", or set `prefix_pattern`, `mention_prefix` or `prefixless_channels`."
+      ));
+    }
+    if self.prefix.chars().any(|c| c.is_whitespace()) {
+      issues.push(ConfigIssue::new(
+        "prefix",
+        "The prefix contains whitespace, which chat clients tend to trim or collapse.",
+        "Remove the whitespace from the prefix."
+      ));
+    }
+    if let Some(pattern) = &self.prefix_pattern {
+      if let Err(e) = Regex::new(pattern) {
+        issues.push(ConfigIssue::new(
+          "prefix_pattern",
+          &format!("\"{}\" isn't a valid regular expression: {}", pattern, e),
+          "Fix the pattern, or remove it to only use `prefix`."
+        ));
+      }
+    }
+  }
+
+  fn validate_repeat_policy(&self, game: SupportedGame, field: &str, issues: &mut Vec<ConfigIssue>) -> () {
+    let policy: RepeatPolicy = self.repeat_limits.for_game(&game);
+    if policy.get_min() > policy.get_max() {
+      issues.push(ConfigIssue::new(
+        field,
+        &format!("`min` ({}) is greater than `max` ({}), so every input would be rejected.", policy.get_min(), policy.get_max()),
+        "Swap them around."
+      ));
+    }
+    let (lowest, highest): (i8, i8) = match game {
+      SupportedGame::FTL => (-8, 8),
+      SupportedGame::NDS => (1, i8::MAX)
+    };
+    if policy.get_min() < &lowest || policy.get_max() > &highest {
+      issues.push(ConfigIssue::new(
+        field,
+        &format!("The range can't go beyond [{}, {}] for {}.", lowest, highest, game.to_string()),
+        "Narrow `min` and `max` to fit."
+      ));
+    }
+  }
+
+  fn open_audit_log(&self) -> Result<Option<Arc<AuditLog>>, ConfigError> {
+    match &self.audit_file {
+      None => return Ok(None),
+      Some(path) => match AuditLog::open(path) {
+        Err(e) => return Err(ConfigError::AuditFile(path.to_string(), e)),
+        Ok(audit_log) => return Ok(Some(Arc::new(audit_log)))
+      }
+    }
+  }
+
+  // Everything a bot needs besides its connection to chat, which is the same
+  // no matter which chat it's connecting to.
+  fn build_dispatcher(&self, watcher: Option<ConfigWatcher>) -> Result<Dispatcher, ConfigError> {
+    let settings: BotSettings = self.build_settings(self.open_audit_log()?)?;
+    let ban_list: BanList = match BanList::load(&self.ban_file) {
+      Err(e) => return Err(ConfigError::BanList(self.ban_file.to_string(), e)),
+      Ok(ban_list) => ban_list
+    };
+    let stats_list: StatsList = match StatsList::load(&self.stats_file) {
+      Err(e) => return Err(ConfigError::StatsFile(self.stats_file.to_string(), e)),
+      Ok(stats_list) => stats_list
+    };
+
+    let metrics_server: Option<Builder<AddrIncoming>> = match &self.metrics_address {
+      None => None,
+      Some(address) => match http::bind(address) {
+        Err(e) => return Err(ConfigError::MetricsAddress(address.to_string(), e)),
+        Ok(server) => Some(server)
+      }
+    };
+    let overlay_server: Option<Builder<AddrIncoming>> = match &self.overlay_address {
+      None => None,
+      Some(address) => match http::bind(address) {
+        Err(e) => return Err(ConfigError::OverlayAddress(address.to_string(), e)),
+        Ok(server) => Some(server)
+      }
+    };
+
+    return Ok(Dispatcher::new(
+      settings,
+      ban_list,
+      stats_list,
+      watcher,
+      metrics_server,
+      Overlay::new(self.overlay_history),
+      overlay_server
+    ));
+  }
+
+  fn get_prefix_matcher(&self) -> Result<PrefixMatcher, ConfigError> {
+    let pattern: Option<Regex> = match &self.prefix_pattern {
+      None => None,
+      Some(pattern) => match Regex::new(pattern) {
+        Err(e) => return Err(ConfigError::InvalidPrefixPattern(pattern.to_string(), e)),
+        Ok(regex) => Some(regex)
+      }
+    };
+    return Ok(PrefixMatcher::new(
+      self.prefix.to_string(),
+      pattern,
+      self.mention_prefix,
+      self.prefixless_channels.clone()
+    ));
   }
 
   #[allow(unreachable_patterns)]
   fn get_msg_parser(&self, game: &SupportedGame)
-    -> Result<Box<dyn MessageParser + Send + Sync>, &str> {
+    -> Result<Box<dyn MessageParser + Send + Sync>, ConfigError> {
     match game {
       SupportedGame::NDS => return Ok(Box::new(NDSUtils)),
       SupportedGame::FTL => return Ok(Box::new(FTLUtils)),
-      _ => return Err("The given game is either invalid or unsupported.")
+      _ => return Err(ConfigError::UnsupportedGame(game.to_string()))
     }
   }
 
-  #[allow(unreachable_patterns)]
-  fn get_focus_checker(&self, game: &SupportedGame)
-    -> Result<Box<dyn GameFocusChecker + Send + Sync>, &str> {
-    match game {
-      SupportedGame::NDS => return Ok(Box::new(NDSUtils)),
-      SupportedGame::FTL => return Ok(Box::new(FTLUtils)),
-      _ => return Err("The given game is either invalid or unsupported.")
+  // Shared between the focus checker and the input backend, since both of
+  // them need to find the game's window.
+  fn get_window_matcher(&self, game: &SupportedGame) -> Result<Arc<WindowMatcher>, ConfigError> {
+    match self.focus_rules.for_game(game).to_matcher() {
+      Err(reason) => return Err(ConfigError::InvalidFocusRule(game.to_string(), reason)),
+      Ok(matcher) => return Ok(Arc::new(matcher))
+    }
+  }
+
+  fn validate_focus_rule(&self, game: SupportedGame, field: &str, issues: &mut Vec<ConfigIssue>) -> () {
+    match self.focus_rules.for_game(&game).to_matcher() {
+      Err(reason) => issues.push(ConfigIssue::new(
+        field,
+        &reason,
+        "Set `kind` to \"Exact\", \"Substring\", \"Regex\", \"Executable\" or \"Pid\" along with a `value`, or to \"Always\"."
+      )),
+      Ok(matcher) => {
+        if self.input_backend.is_targeted() && !matcher.is_specific() {
+          issues.push(ConfigIssue::new(
+            field,
+            "The rule matches every window, so there's no telling which one to send inputs to.",
+            "Use another kind of rule, or set `input_backend` to \"Global\"."
+          ));
+        }
+      }
     }
   }
 }
@@ -66,14 +354,43 @@ impl AppModelSerenity {
 impl Default for AppModelSerenity {
   fn default() -> AppModelSerenity {
     AppModelSerenity {
+      platform: Platform::Discord,
       prefix: ";".to_string(),
-      token: "".to_string(),
+      prefix_pattern: None,
+      mention_prefix: false,
+      prefixless_channels: Vec::new(),
+      token_env: None,
+      token_file: None,
+      token: None,
       /*
       The config file wouldn't be created properly until I set this to
       something. Huh. It's hardly reasonable to already have this set to
       whatever, but better than nothing, I guess.
       */
-      game: Some(SupportedGame::NDS)
+      game: Some(SupportedGame::NDS),
+      admin_roles: Vec::new(),
+      admin_users: Vec::new(),
+      allowed_guilds: Vec::new(),
+      allowed_channels: Vec::new(),
+      dm_policy: DmPolicy::Allow,
+      ban_file: "./bans.toml".to_string(),
+      stats_file: "./stats.toml".to_string(),
+      input_backend: InputBackendKind::Global,
+      focus_loss_policy: FocusLossPolicy::Hold,
+      focus_loss_timeout: 30,
+      status_channel: None,
+      log_level: LogLevel::Info,
+      library_log_level: LogLevel::Warn,
+      log_file: None,
+      queue_feedback: QueueFeedback::Off,
+      max_queue: 0,
+      audit_file: None,
+      metrics_address: None,
+      overlay_address: None,
+      overlay_history: 10,
+      repeat_limits: RepeatLimits::default(),
+      focus_rules: FocusRules::default(),
+      irc: IrcConfig::default()
     }
   }
 }
@@ -81,39 +398,164 @@ impl Default for AppModelSerenity {
 #[async_trait]
 impl AppModel for AppModelSerenity {
 
-  async fn start(&self) -> Result<(), String> {
-    match self.build_bot().await {
-      Err(e) => return Err(e.to_string()),
-      Ok(mut bot) => match bot.start().await {
-        Err(e) => return Err(e),
-        Ok(_) => return Ok(())
-      }
+  async fn build_bot(&self, watcher: Option<ConfigWatcher>) -> Result<Box<dyn Bot + Send + Sync>, BotError> {
+    self.validate()?;
+    match self.platform {
+      Platform::Discord => {
+        let token: Secret = self.resolve_token()?;
+        return Ok(Box::new(BotSerenity::new(token, self.build_dispatcher(watcher)?).await?));
+      },
+      Platform::Irc => return Ok(Box::new(BotIrc::new(self.build_dispatcher(watcher)?, self.irc.clone())))
     }
   }
 
-  async fn build_bot(&self) -> Result<Box<dyn Bot + Send + Sync>, &str> {
-    match self.game {
-      None => return Err("Cannot build a bot without a supported game."),
-      Some(game) => match self.get_msg_parser(&game) {
-        Err(e) => return Err(e),
-        Ok(msg_parser) => match self.get_focus_checker(&game) {
-            Err(e) => return Err(e),
-            Ok(focus_checker) => match self.token.to_string().as_str() {
-              "" => return Err("Cannot build a bot without an OAuth token."),
-              _ => {
-                return Ok(Box::new(
-                  BotSerenity::new(
-                    self.prefix.to_string(),
-                    self.token.to_string(),
-                    msg_parser,
-                    focus_checker)
-                    .await
-                ));
-              }
-            }
-          }
+  async fn build_console(&self, watcher: Option<ConfigWatcher>, script: Option<String>, user: String)
+    -> Result<Box<dyn Bot + Send + Sync>, BotError> {
+    self.check(false)?;
+    return Ok(Box::new(BotConsole::new(Arc::new(self.build_dispatcher(watcher)?), script, user)));
+  }
+
+  fn build_settings(&self, audit_log: Option<Arc<AuditLog>>) -> Result<BotSettings, ConfigError> {
+    let game: SupportedGame = match self.game {
+      None => return Err(ConfigError::MissingGame),
+      Some(game) => game
+    };
+    let msg_parser: Box<dyn MessageParser + Send + Sync> = self.get_msg_parser(&game)?;
+    let matcher: Arc<WindowMatcher> = self.get_window_matcher(&game)?;
+    let focus_checker: Box<dyn GameFocusChecker + Send + Sync> = Box::new(
+      RuleFocusChecker::new(Arc::clone(&matcher), self.input_backend.is_targeted())
+    );
+    let prefix: PrefixMatcher = self.get_prefix_matcher()?;
+    // Depowering in FTL holds Shift, so without a way to hold it, negative
+    // counts would power systems up instead. They're rejected like any other
+    // count that's out of range.
+    let repeat_policy: RepeatPolicy = match self.input_backend.can_hold_modifiers() {
+      true => self.repeat_limits.for_game(&game),
+      false => self.repeat_limits.for_game(&game).positive_only()
+    };
+    return Ok(BotSettings::new(
+      Box::new(self.clone()),
+      prefix,
+      game,
+      msg_parser,
+      repeat_policy,
+      focus_checker,
+      FocusLoss::new(self.focus_loss_policy, self.focus_loss_timeout),
+      self.input_backend.to_backend(matcher),
+      AdminList::new(
+        self.admin_roles.clone(),
+        self.admin_users.clone()
+      ),
+      CommandScope::new(
+        self.allowed_guilds.clone(),
+        self.allowed_channels.clone(),
+        self.dm_policy
+      ),
+      self.status_channel,
+      audit_log,
+      self.queue_feedback,
+      self.max_queue
+    ));
+  }
+
+  fn validate(&self) -> Result<(), ConfigError> {
+    return self.check(true);
+  }
+
+  fn check(&self, connects: bool) -> Result<(), ConfigError> {
+    let mut issues: Vec<ConfigIssue> = Vec::new();
+
+    if self.game.is_none() {
+      issues.push(ConfigIssue::new(
+        "game",
+        "No game is set.",
+        "Set it to either \"FTL\" or \"NDS\"."
+      ));
+    }
+    match (connects, self.platform) {
+      (false, _) => (),
+      (true, Platform::Discord) => {
+        if let Err(e) = self.resolve_token() {
+          issues.push(ConfigIssue::new(
+            "token",
+            &e.to_string(),
+            "The token can be found on your bot's page in the Discord developer portal."
+          ));
+        }
+      },
+      (true, Platform::Irc) => self.irc.check(&mut issues)
+    }
+    self.validate_prefix(&mut issues);
+    if let DmPolicy::AdminsOnly = self.dm_policy {
+      if self.admin_users.is_empty() {
+        issues.push(ConfigIssue::new(
+          "dm_policy",
+          "Direct messages are limited to admins, but roles don't exist in direct messages and `admin_users` is empty.",
+          "Add your user ID to `admin_users`, or set `dm_policy` to \"Ignore\"."
+        ));
+      }
+    }
+    if self.ban_file.is_empty() {
+      issues.push(ConfigIssue::new(
+        "ban_file",
+        "No path is set, so bans can't be saved.",
+        "Set it to something like \"./bans.toml\"."
+      ));
+    }
+    if let Some(address) = &self.metrics_address {
+      if address.parse::<SocketAddr>().is_err() {
+        issues.push(ConfigIssue::new(
+          "metrics_address",
+          &format!("\"{}\" isn't an address with a port.", address),
+          "Set it to something like \"127.0.0.1:9100\", or remove it to turn metrics off."
+        ));
+      }
+    }
+    if let Some(address) = &self.overlay_address {
+      if address.parse::<SocketAddr>().is_err() {
+        issues.push(ConfigIssue::new(
+          "overlay_address",
+          &format!("\"{}\" isn't an address with a port.", address),
+          "Set it to something like \"127.0.0.1:9200\", or remove it to turn the overlay off."
+        ));
+      }
+    }
+    if self.stats_file.is_empty() {
+      issues.push(ConfigIssue::new(
+        "stats_file",
+        "No path is set, so stats can't be saved.",
+        "Set it to something like \"./stats.toml\"."
+      ));
+    }
+    if let FocusLossPolicy::DropAfter = self.focus_loss_policy {
+      if self.focus_loss_timeout == 0 {
+        issues.push(ConfigIssue::new(
+          "focus_loss_timeout",
+          "The timeout is 0, so inputs would be dropped as soon as the game loses focus.",
+          "Set it to a number of seconds, or set `focus_loss_policy` to \"Drop\"."
+        ));
       }
     }
+    self.validate_repeat_policy(SupportedGame::FTL, "repeat_limits.ftl", &mut issues);
+    self.validate_repeat_policy(SupportedGame::NDS, "repeat_limits.nds", &mut issues);
+    self.validate_focus_rule(SupportedGame::FTL, "focus_rules.ftl", &mut issues);
+    self.validate_focus_rule(SupportedGame::NDS, "focus_rules.nds", &mut issues);
+
+    match issues.is_empty() {
+      true => return Ok(()),
+      false => return Err(ConfigError::Invalid(issues))
+    }
+  }
+
+  fn build_logger(&self) -> Result<Logger, ConfigError> {
+    let file: Option<File> = match &self.log_file {
+      None => None,
+      Some(path) => match OpenOptions::new().create(true).append(true).open(path) {
+        Err(e) => return Err(ConfigError::LogFile(path.to_string(), e)),
+        Ok(file) => Some(file)
+      }
+    };
+    return Ok(build_logger(self.log_level, self.library_log_level, file));
   }
 
   fn get_prefix(&self) -> &String {
@@ -124,21 +566,31 @@ impl AppModel for AppModelSerenity {
     return Box::new(
       AppModelSerenity {
         prefix: prefix,
-        token: self.token.to_string(),
-        game: self.game
+        ..self.clone()
     });
   }
 
-  fn get_token(&self) -> &String {
+  fn get_token(&self) -> &Option<Secret> {
     return &self.token;
   }
 
-  fn set_token(&self, token: String) -> Box<dyn AppModel> {
+  fn set_token(&self, token: Option<Secret>) -> Box<dyn AppModel> {
     return Box::new(
       AppModelSerenity {
-        prefix: self.prefix.to_string(),
         token: token,
-        game: self.game
+        ..self.clone()
+    });
+  }
+
+  fn get_token_env(&self) -> &Option<String> {
+    return &self.token_env;
+  }
+
+  fn set_token_env(&self, token_env: Option<String>) -> Box<dyn AppModel> {
+    return Box::new(
+      AppModelSerenity {
+        token_env: token_env,
+        ..self.clone()
     });
   }
 
@@ -149,9 +601,20 @@ impl AppModel for AppModelSerenity {
   fn set_game(&self, game: Option<SupportedGame>) -> Box<dyn AppModel> {
     return Box::new(
       AppModelSerenity {
-        prefix: self.prefix.to_string(),
-        token: self.token.to_string(),
-        game: game
+        game: game,
+        ..self.clone()
+    });
+  }
+
+  fn get_input_backend(&self) -> &InputBackendKind {
+    return &self.input_backend;
+  }
+
+  fn set_input_backend(&self, input_backend: InputBackendKind) -> Box<dyn AppModel> {
+    return Box::new(
+      AppModelSerenity {
+        input_backend: input_backend,
+        ..self.clone()
     });
   }
 }
\ No newline at end of file
@@ -0,0 +1,240 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::{
+  app_model::AppModel,
+  error::{AuditError, BotError},
+  key::KeyInputtable,
+  queue::QueuedInput,
+  settings::BotSettings
+};
+use inputbot::KeybdKey;
+use serde_json::{Value, json};
+use tracing::warn;
+use std::{
+  collections::HashMap,
+  convert::TryFrom,
+  fs::{self, File, OpenOptions},
+  io::{self, Write},
+  str::FromStr,
+  sync::{
+    Mutex,
+    mpsc::{Receiver, Sender, channel}
+  },
+  thread::{self, sleep},
+  time::{Duration, SystemTime, UNIX_EPOCH}
+};
+
+fn unix_now_millis() -> u64 {
+  return SystemTime::now().duration_since(UNIX_EPOCH)
+    .map(|now| now.as_millis() as u64)
+    .unwrap_or(0);
+}
+
+// Keys are written by name rather than by code, since codes differ between
+// platforms.
+fn key_names(keys: &Vec<KeybdKey>) -> Vec<String> {
+  return keys.iter().map(|key| format!("{:?}", key)).collect();
+}
+
+// One input that was executed, with everything needed to settle who did what,
+// or to do it all over again.
+pub struct AuditEntry {
+  // Milliseconds since the Unix epoch, as of when the input finished.
+  time: u64,
+  game: SupportedGame,
+  user_id: u64,
+  user: String,
+  // The message exactly as it was sent, and the command it was parsed from.
+  message: String,
+  command: String,
+  keys: Vec<String>,
+  presses: i8
+}
+
+impl AuditEntry {
+  pub fn new(game: SupportedGame, queued: &QueuedInput) -> AuditEntry {
+    return AuditEntry {
+      time: unix_now_millis(),
+      game: game,
+      user_id: *queued.get_user_id(),
+      user: queued.get_user().to_string(),
+      message: queued.get_message().to_string(),
+      command: queued.get_command().to_string(),
+      keys: key_names(&queued.get_input().get_keys()),
+      presses: *queued.get_presses()
+    }
+  }
+
+  fn to_json(&self) -> Value {
+    return json!({
+      "time": self.time,
+      "game": self.game.to_id(),
+      // IDs are too big for some JSON readers to handle as numbers.
+      "user_id": self.user_id.to_string(),
+      "user": self.user,
+      "message": self.message,
+      "command": self.command,
+      "keys": self.keys,
+      "presses": self.presses
+    });
+  }
+
+  fn from_json(entry: &Value) -> Option<AuditEntry> {
+    return Some(AuditEntry {
+      time: entry["time"].as_u64()?,
+      game: SupportedGame::from_str(entry["game"].as_str()?).ok()?,
+      user_id: entry["user_id"].as_str()?.parse::<u64>().ok()?,
+      user: entry["user"].as_str()?.to_string(),
+      message: entry["message"].as_str()?.to_string(),
+      command: entry["command"].as_str()?.to_string(),
+      keys: entry["keys"].as_array()?.iter()
+        .map(|key| key.as_str().map(|key| key.to_string()))
+        .collect::<Option<Vec<String>>>()?,
+      presses: i8::try_from(entry["presses"].as_i64()?).ok()?
+    });
+  }
+}
+
+// Executed inputs, one JSON object per line. Entries are only ever added to the
+// end, so nothing that's been written can be lost to a crash partway through.
+pub struct AuditLog {
+  // Every entry is synced to disk before the next, which is slow enough that
+  // it's left to a thread of its own rather than whatever executed the input.
+  // Entries still go out in the order they came in.
+  writer: Mutex<Sender<AuditWrite>>
+}
+
+enum AuditWrite {
+  Entry(AuditEntry),
+  // Answered once everything sent before it has been written.
+  Flush(Sender<()>)
+}
+
+fn write_entries(mut file: File, writes: Receiver<AuditWrite>) -> () {
+  // This ends once the log's dropped, like when the settings are reloaded, and
+  // everything that was already sent has been written.
+  for write in writes {
+    match write {
+      AuditWrite::Flush(done) => {
+        let _ = done.send(());
+      },
+      AuditWrite::Entry(entry) => {
+        let written: Result<(), io::Error> = writeln!(file, "{}", entry.to_json()).and_then(|_| file.sync_data());
+        if let Err(e) = written {
+          warn!(error = %e, user = %entry.user, command = %entry.command, "Couldn't add an input to the audit log");
+        }
+      }
+    }
+  }
+}
+
+impl AuditLog {
+  pub fn open(path: &str) -> Result<AuditLog, io::Error> {
+    let file: File = OpenOptions::new().create(true).append(true).open(path)?;
+    let (sender, receiver) = channel::<AuditWrite>();
+    thread::spawn(move || write_entries(file, receiver));
+    return Ok(AuditLog {
+      writer: Mutex::new(sender)
+    });
+  }
+
+  fn send(&self, write: AuditWrite) -> () {
+    // This only fails if the writer's gone, which it can't be while the log
+    // is still around.
+    let _ = self.writer.lock().expect("Expected the audit log's lock to not be poisoned.").send(write);
+  }
+
+  pub fn append(&self, entry: AuditEntry) -> () {
+    self.send(AuditWrite::Entry(entry));
+  }
+
+  // Waits for every entry appended so far to be written, for when the bot's
+  // about to exit.
+  pub fn flush(&self) -> () {
+    let (done, flushed) = channel::<()>();
+    self.send(AuditWrite::Flush(done));
+    let _ = flushed.recv();
+  }
+}
+
+pub fn read_audit_log(path: &str) -> Result<Vec<AuditEntry>, AuditError> {
+  let contents: String = match fs::read_to_string(path) {
+    Err(e) => return Err(AuditError::Read(path.to_string(), e)),
+    Ok(contents) => contents
+  };
+  let mut entries: Vec<AuditEntry> = Vec::new();
+  for (i, line) in contents.lines().enumerate() {
+    if line.trim().is_empty() {
+      continue;
+    }
+    match serde_json::from_str::<Value>(line).ok().as_ref().and_then(AuditEntry::from_json) {
+      None => return Err(AuditError::Entry(path.to_string(), i + 1)),
+      Some(entry) => entries.push(entry)
+    }
+  }
+  return Ok(entries);
+}
+
+// Executes every entry again through the config's input backend, waiting
+// between them as long as they originally waited, divided by the speed. A
+// speed of 0 doesn't wait at all.
+//
+// Every command is parsed again rather than trusting the logged keys, and any
+// that resolve to different keys than they used to are reported, so a log
+// doubles as a test for changes to the parsers.
+pub fn replay(cfg: &Box<dyn AppModel>, entries: &Vec<AuditEntry>, speed: f64) -> Result<(), BotError> {
+  // Entries can be for any game, so settings are built for each one as it
+  // comes up.
+  let mut settings: HashMap<&'static str, BotSettings> = HashMap::new();
+  let mut mismatches: usize = 0;
+  let mut last_time: Option<u64> = None;
+
+  for (i, entry) in entries.iter().enumerate() {
+    if let Some(last_time) = last_time {
+      if speed > 0.0 {
+        let waited: u64 = entry.time.saturating_sub(last_time);
+        sleep(Duration::from_secs_f64(waited as f64 / 1000.0 / speed));
+      }
+    }
+    last_time = Some(entry.time);
+
+    if !settings.contains_key(entry.game.to_id()) {
+      settings.insert(entry.game.to_id(), cfg.set_game(Some(entry.game)).build_settings(None)?);
+    }
+    let game_settings: &BotSettings = &settings[entry.game.to_id()];
+
+    println!("[{}/{}] {}: {}", i + 1, entries.len(), entry.user, entry.command);
+    let input: Box<dyn KeyInputtable + Send + Sync> =
+      match game_settings.get_msg_parser().parse_msg(&entry.command) {
+        Err(e) => {
+          eprintln!("  Couldn't parse the command anymore: {}", e);
+          mismatches += 1;
+          continue;
+        },
+        Ok(input) => input
+      };
+    let keys: Vec<String> = key_names(&input.get_keys());
+    if keys != entry.keys || *input.get_presses() != entry.presses {
+      eprintln!(
+        "  Resolved to {:?} x{}, but was logged as {:?} x{}.",
+        keys, input.get_presses(), entry.keys, entry.presses
+      );
+      mismatches += 1;
+    }
+
+    let mut next: Option<Box<dyn KeyInputtable + Send + Sync>> = Some(input);
+    while let Some(input) = next {
+      next = match input.pop(&**game_settings.get_backend()) {
+        Err(e) => {
+          eprintln!("  Couldn't press the input: {}", e);
+          None
+        },
+        Ok(next) => next
+      };
+    }
+  }
+
+  match mismatches {
+    0 => return Ok(()),
+    mismatches => return Err(AuditError::Mismatch(mismatches, entries.len()).into())
+  }
+}
\ No newline at end of file
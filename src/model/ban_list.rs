@@ -0,0 +1,124 @@
+use confy::ConfyError;
+use serde::{Serialize, Deserialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn unix_now() -> u64 {
+  return SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .expect("Expected the system clock to be set after 1970.")
+    .as_secs();
+}
+
+// Parses durations like "30s", "10m", "2h" or "1d" into seconds. Durations too
+// long to count in seconds are an error rather than wrapping around.
+pub fn parse_duration(s: &str) -> Result<u64, ()> {
+  // The unit is split off by character, since chat can end a message with
+  // anything, including characters that take more than one byte.
+  let (split, unit): (usize, char) = match s.char_indices().last() {
+    None => return Err(()),
+    Some(last) => last
+  };
+  let multiplier: u64 = match unit {
+    's' => 1,
+    'm' => 60,
+    'h' => 60 * 60,
+    'd' => 60 * 60 * 24,
+    _ => return Err(())
+  };
+  match s[..split].parse::<u64>() {
+    Err(_) => return Err(()),
+    Ok(amount) => return amount.checked_mul(multiplier).ok_or(())
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ban {
+  user: u64,
+  // A Unix timestamp in seconds. Bans without one last until they're lifted.
+  expires: Option<u64>
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BanList {
+  #[serde(skip)]
+  path: String,
+  bans: Vec<Ban>
+}
+
+impl BanList {
+  pub fn load(path: &str) -> Result<BanList, ConfyError> {
+    let mut ban_list: BanList = confy::load_path(path)?;
+    ban_list.path = path.to_string();
+    return Ok(ban_list);
+  }
+
+  pub fn save(&self) -> Result<(), ConfyError> {
+    return confy::store_path(&self.path, self);
+  }
+
+  // Bans replace any existing ban on the same user, so re-banning someone
+  // can be used to extend or shorten their ban.
+  pub fn ban(&mut self, user: u64, expires: Option<u64>) -> () {
+    self.unban(user);
+    self.bans.push(Ban { user: user, expires: expires });
+  }
+
+  pub fn unban(&mut self, user: u64) -> bool {
+    let before: usize = self.bans.len();
+    self.bans.retain(|ban| ban.user != user);
+    return self.bans.len() != before;
+  }
+
+  pub fn is_banned(&self, user: u64, now: u64) -> bool {
+    return self.bans.iter().any(|ban| ban.user == user && match ban.expires {
+      None => true,
+      Some(expires) => expires > now
+    });
+  }
+
+  // Returns whether any bans were actually removed, so we only bother saving
+  // the list when something changed.
+  pub fn remove_expired(&mut self, now: u64) -> bool {
+    let before: usize = self.bans.len();
+    self.bans.retain(|ban| match ban.expires {
+      None => true,
+      Some(expires) => expires > now
+    });
+    return self.bans.len() != before;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_each_unit() {
+    assert_eq!(parse_duration("30s"), Ok(30));
+    assert_eq!(parse_duration("10m"), Ok(600));
+    assert_eq!(parse_duration("2h"), Ok(7200));
+    assert_eq!(parse_duration("1d"), Ok(86400));
+  }
+
+  #[test]
+  fn rejects_missing_parts() {
+    assert_eq!(parse_duration(""), Err(()));
+    assert_eq!(parse_duration("s"), Err(()));
+    assert_eq!(parse_duration("30"), Err(()));
+    assert_eq!(parse_duration("-5m"), Err(()));
+  }
+
+  #[test]
+  fn rejects_multibyte_units() {
+    assert_eq!(parse_duration("5é"), Err(()));
+    assert_eq!(parse_duration("é"), Err(()));
+    assert_eq!(parse_duration("5ém"), Err(()));
+  }
+
+  #[test]
+  fn rejects_overflowing_durations() {
+    assert_eq!(parse_duration(&format!("{}s", u64::MAX)), Ok(u64::MAX));
+    assert_eq!(parse_duration(&format!("{}d", u64::MAX / 60)), Err(()));
+    assert_eq!(parse_duration("99999999999999999999999s"), Err(()));
+  }
+}
\ No newline at end of file
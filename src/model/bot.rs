@@ -1,4 +1,12 @@
-use crate::model::key::KeyInputtable;
+use crate::model::{
+  chat::{Chat, ChatMessage, StatusUpdate},
+  dispatcher::Dispatcher,
+  error::{ParseError, PlatformError},
+  key::KeyInputtable,
+  secret::Secret,
+  settings::BotSettings
+};
+use serde::{Serialize, Deserialize};
 use serenity::{
   async_trait,
   client::{
@@ -6,195 +14,219 @@ use serenity::{
     Context,
     EventHandler
   },
+  http::Http,
   framework::standard::StandardFramework,
   model::{
     channel::{
       Message,
       ReactionType
     },
-    id::GuildId,
+    id::{ChannelId, GuildId, MessageId},
     gateway::{
       Ready,
       Activity
     }
   }
 };
-use std::{
-  sync::{
-    Arc,
-    atomic::{
-      AtomicBool,
-      Ordering
-    }
-  },
-  time::Duration
-};
-use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use typemap_rev::{TypeMap, TypeMapKey};
+use std::sync::Arc;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tracing::info;
+
+// Which chat the bot connects to. Everything besides the connection itself, like
+// the queue and commands, works the same on all of them.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum Platform {
+  Discord,
+  Irc
+}
 
 #[async_trait]
 pub trait Bot {
-  async fn start(&mut self) -> Result<(), String>;
+  async fn start(&mut self) -> Result<(), PlatformError>;
+
+  // Makes sure everything's on disk, for when the bot's about to exit.
+  async fn flush(&self) -> ();
 }
 
 pub trait MessageParser {
-  fn parse_msg(&self, content: &str) -> Result<Box<dyn KeyInputtable + Send + Sync>, ()>;
+  fn parse_msg(&self, content: &str) -> Result<Box<dyn KeyInputtable + Send + Sync>, ParseError>;
+
+  // Every command name the game understands, used to suggest fixes for typos.
+  fn aliases(&self) -> Vec<&'static str>;
 }
 
 pub trait GameFocusChecker {
   fn game_focused(&self) -> bool;
 }
 
-struct InputStack;
+// Inputs can be queued and executed faster than Discord can take reactions, so
+// updates all go through one queue. That way, a message can't end up with its
+// executed reaction before its queued one, and the input loop never waits on
+// Discord.
+async fn update_reactions(http: Arc<Http>, mut updates: UnboundedReceiver<StatusUpdate>) -> () {
+  while let Some(update) = updates.recv().await {
+    let channel_id: ChannelId = ChannelId(update.channel_id);
+    if let Some(replaces) = update.replaces {
+      let reaction: ReactionType = ReactionType::Unicode(replaces.emoji().to_string());
+      let _ = channel_id.delete_reaction(&http, update.message_id, None, reaction).await;
+    }
+    let reaction: ReactionType = ReactionType::Unicode(update.status.emoji().to_string());
+    let _ = channel_id.create_reaction(&http, update.message_id, reaction).await;
+  }
+}
 
-impl TypeMapKey for InputStack {
-  type Value = Arc<RwLock<Vec<Box<dyn KeyInputtable + Send + Sync>>>>;
+// Talks back to Discord through whichever context the event came with.
+struct DiscordChat {
+  ctx: Context,
+  reactions: UnboundedSender<StatusUpdate>
 }
 
-struct FocusChecker;
+#[async_trait]
+impl Chat for DiscordChat {
+  async fn say(&self, channel_id: u64, content: &str) -> () {
+    let _ = ChannelId(channel_id).say(&self.ctx.http, content).await;
+  }
 
-impl TypeMapKey for FocusChecker {
-  type Value = Arc<RwLock<Box<dyn GameFocusChecker + Send + Sync>>>;
-}
+  // Replies don't ping, the same as Message::reply.
+  async fn reply(&self, msg: &ChatMessage, content: &str) -> () {
+    let channel_id: ChannelId = ChannelId(*msg.get_channel_id());
+    let _ = channel_id.send_message(&self.ctx.http, |m| m
+      .content(content)
+      .reference_message((channel_id, MessageId(*msg.get_id())))
+      .allowed_mentions(|a| a.replied_user(false))
+    ).await;
+  }
 
-// Convenience function for retrieving the Arc wrapper around the input stack.
-// This will allow us to safely write to the stack.
-async fn arc_input_stack(ctx: &Context)
-  -> Arc<RwLock<Vec<Box<dyn KeyInputtable + Send + Sync>>>> {
-  let data_read: RwLockReadGuard<'_, TypeMap> = ctx.data.read().await;
-  return data_read.get::<InputStack>().expect("Expected an InputStack in TypeMap.").clone();
-}
+  async fn react(&self, msg: &ChatMessage, emoji: &str) -> () {
+    let reaction: ReactionType = ReactionType::Unicode(emoji.to_string());
+    let _ = ChannelId(*msg.get_channel_id()).create_reaction(&self.ctx.http, *msg.get_id(), reaction).await;
+  }
 
-async fn arc_focus_checker(ctx: &Context)
-  -> Arc<RwLock<Box<dyn GameFocusChecker + Send + Sync>>> {
-  let data_read: RwLockReadGuard<'_, TypeMap> = ctx.data.read().await;
-  return data_read.get::<FocusChecker>().expect("Expected a FocusChecker in TypeMap.").clone(); 
-}
+  fn update_status(&self, update: StatusUpdate) -> () {
+    // This only fails once the bot's shutting down, at which point nobody's
+    // looking at reactions anyways.
+    let _ = self.reactions.send(update);
+  }
+
+  async fn set_activity(&self, activity: &str) -> () {
+    self.ctx.set_activity(Activity::playing(activity)).await;
+  }
 
-// A function representing a command for a generic input.
-// In general, the idea is that it'll pass the message content to the parser,
-// after which it's supposed to retrieve an input to add to the stack.
-async fn push_input(msg_parser: &Box<dyn MessageParser + Send + Sync>, ctx: &Context, content: &str) -> () {
-  let input_stack_lock = arc_input_stack(ctx).await;
-
-  // This enables us to write to the input stack without deadlocking the bot.
-  // The only problem is that this is obnoxiously ugly.
-  let mut input_stack:
-    RwLockWriteGuard<Vec<Box<dyn KeyInputtable + Send + Sync>>> =
-    input_stack_lock.write().await;
-  match msg_parser.parse_msg(content) {
-    Err(_) => (),
-    Ok(input) => input_stack.insert(0, input)
+  async fn bot_user_id(&self) -> Option<u64> {
+    return Some(self.ctx.cache.current_user_id().await.0);
   }
 }
 
-async fn pop_input(ctx: Arc<Context>) -> () {
-  let input_stack_lock = arc_input_stack(&ctx).await;
-  let focus_checker_lock = arc_focus_checker(&ctx).await;
-
-  let mut input_stack:
-    RwLockWriteGuard<Vec<Box<dyn KeyInputtable + Send + Sync>>> =
-    input_stack_lock.write().await;
-  let focus_checker = focus_checker_lock.write().await;
-  
-  // I enjoy pattern matching in this language though.
-  if focus_checker.game_focused() {
-    match input_stack.pop() {
-      None => return,
-      Some(input) => {
-        if input.get_presses() <= &20 {
-          match input.pop() {
-            None => return,
-            Some(next) => input_stack.push(next)
-          }
-        }
-      }
+struct Handler {
+  dispatcher: Arc<Dispatcher>,
+  reactions: UnboundedSender<StatusUpdate>
+}
+
+impl Handler {
+  fn chat(&self, ctx: Context) -> DiscordChat {
+    return DiscordChat {
+      ctx: ctx,
+      reactions: self.reactions.clone()
     }
   }
 }
 
-struct Handler {
-  prefix: String,
-  msg_parser: Box<dyn MessageParser + Send + Sync>,
-  input_loop_running: AtomicBool
+fn is_admin(settings: &BotSettings, msg: &Message) -> bool {
+  let role_ids: Vec<u64> = match &msg.member {
+    None => Vec::new(),
+    Some(member) => member.roles.iter().map(|role_id| role_id.0).collect()
+  };
+  return settings.get_admins().is_admin(msg.author.id.0, &role_ids);
 }
 
 #[async_trait]
 impl EventHandler for Handler {
   async fn message(&self, ctx: Context, msg: Message) -> () {
-    if &msg.content.len() > &1
-      && &msg.content.chars().nth(0).unwrap().to_string() == &self.prefix {
-      let slice: &str = &msg.content[1..];
-      push_input(&self.msg_parser, &ctx, &slice).await;
-      &msg.react(&ctx.http, ReactionType::Unicode("☑️".to_string())).await;
+    // Prefix-less channels would otherwise have the bot reading its own
+    // replies as commands.
+    if msg.author.bot {
+      return;
+    }
+
+    // Guilds and roles are Discord's own thing, so they're sorted out here
+    // rather than in the dispatcher.
+    let settings: Arc<BotSettings> = self.dispatcher.current_settings().await;
+    let guild_id: Option<u64> = msg.guild_id.map(|guild_id| guild_id.0);
+    let admin: bool = is_admin(&settings, &msg);
+    if !settings.get_scope().allows(guild_id, msg.channel_id.0, admin) {
+      return;
     }
+
+    let chat_msg: ChatMessage = ChatMessage::new(
+      msg.id.0,
+      msg.channel_id.0,
+      msg.author.id.0,
+      msg.author.name.to_string(),
+      admin,
+      msg.content.to_string()
+    );
+    self.dispatcher.handle_message(&self.chat(ctx), chat_msg).await;
   }
 
-  async fn ready(&self, ctx: Context, _ready: Ready) {
-    let ctx = Arc::new(ctx);
-    ctx.set_activity(
-      Activity::listening(
-        format!("the prefix \"{}\"", self.prefix)
-      )
-    ).await;
+  async fn ready(&self, ctx: Context, ready: Ready) {
+    info!(user = %ready.user.name, "Connected to Discord");
+    self.chat(ctx).set_activity(&self.dispatcher.activity().await).await;
   }
 
   // I don't totally know what this does, but it supposedly allows you to run
   // another loop at the same time as the bot's current thread.
   async fn cache_ready(&self, ctx: Context, _guilds: Vec<GuildId>) {
-    let ctx = Arc::new(ctx);
-    if !self.input_loop_running.load(Ordering::Relaxed) {
-      let ctx_clone = Arc::clone(&ctx);
-
-      tokio::spawn(async move {
-        loop {
-          pop_input(Arc::clone(&ctx_clone)).await;
-          tokio::time::sleep(Duration::from_millis(1)).await;
-        }
-      });
-    }
+    self.dispatcher.start(Arc::new(self.chat(ctx)));
   }
 }
 
 pub struct BotSerenity {
-  client: Client
+  client: Client,
+  dispatcher: Arc<Dispatcher>,
+  // Taken out once the bot starts, since that's when there's an HTTP client to
+  // make the changes with.
+  reaction_updates: Option<UnboundedReceiver<StatusUpdate>>
 }
 
 impl BotSerenity {
 
-  pub async fn new(prefix: String, token: String, msg_parser: Box<dyn MessageParser + Send + Sync>, focus_checker: Box<dyn GameFocusChecker + Send + Sync>) -> BotSerenity {
+  pub async fn new(token: Secret, dispatcher: Dispatcher) -> Result<BotSerenity, PlatformError> {
     let framework = StandardFramework::new()
       .configure(|c| c
         .with_whitespace(true));
 
-    let mut bot: BotSerenity = BotSerenity {
-      client: Client::builder(token)
+    let dispatcher: Arc<Dispatcher> = Arc::new(dispatcher);
+    let (reaction_sender, reaction_receiver) = unbounded_channel::<StatusUpdate>();
+    return Ok(BotSerenity {
+      client: Client::builder(token.expose())
         .event_handler(Handler {
-          prefix: prefix.to_string(),
-          msg_parser: msg_parser,
-          input_loop_running: AtomicBool::new(false)
+          dispatcher: Arc::clone(&dispatcher),
+          reactions: reaction_sender
         })
         .framework(framework)
         .await
-        .expect("Error creating client.")
-    };
-  
-    {
-      let mut data = (&mut bot).client.data.write().await;
-      data.insert::<InputStack>(Arc::new(RwLock::new(Vec::new())));
-      data.insert::<FocusChecker>(Arc::new(RwLock::new(focus_checker)));
-    }
-
-    return bot;
+        .map_err(|e| PlatformError::Client(e.to_string()))?,
+      dispatcher: dispatcher,
+      reaction_updates: Some(reaction_receiver)
+    });
   }
 }
 
 #[async_trait]
 impl Bot for BotSerenity {
-  async fn start(&mut self) -> Result<(), String> {
+  async fn start(&mut self) -> Result<(), PlatformError> {
+    if let Some(updates) = self.reaction_updates.take() {
+      tokio::spawn(update_reactions(Arc::clone(&self.client.cache_and_http.http), updates));
+    }
+    self.dispatcher.serve();
+
     match self.client.start().await {
-      Err(e) => return Err(e.to_string()),
+      Err(e) => return Err(PlatformError::Connection(e.to_string())),
       Ok(_) => return Ok(())
     }
   }
+
+  async fn flush(&self) -> () {
+    self.dispatcher.flush().await;
+  }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+use crate::model::{
+  admin::parse_user_mention,
+  input_status::InputStatus
+};
+use serenity::async_trait;
+
+// A message from whichever chat platform the bot is running on, boiled down to
+// what the dispatcher actually needs.
+pub struct ChatMessage {
+  id: u64,
+  channel_id: u64,
+  user_id: u64,
+  user: String,
+  // Worked out by the platform, since only it knows about things like roles.
+  admin: bool,
+  content: String
+}
+
+impl ChatMessage {
+  pub fn new(id: u64, channel_id: u64, user_id: u64, user: String, admin: bool, content: String) -> ChatMessage {
+    return ChatMessage {
+      id: id,
+      channel_id: channel_id,
+      user_id: user_id,
+      user: user,
+      admin: admin,
+      content: content
+    }
+  }
+
+  pub fn get_id(&self) -> &u64 {
+    return &self.id;
+  }
+
+  pub fn get_channel_id(&self) -> &u64 {
+    return &self.channel_id;
+  }
+
+  pub fn get_user_id(&self) -> &u64 {
+    return &self.user_id;
+  }
+
+  pub fn get_user(&self) -> &String {
+    return &self.user;
+  }
+
+  pub fn is_admin(&self) -> bool {
+    return self.admin;
+  }
+
+  pub fn get_content(&self) -> &String {
+    return &self.content;
+  }
+}
+
+// A change to the status shown on an input's message. Each status replaces the
+// last one, if there was one.
+pub struct StatusUpdate {
+  pub channel_id: u64,
+  pub message_id: u64,
+  pub replaces: Option<InputStatus>,
+  pub status: InputStatus
+}
+
+// Everything the dispatcher needs to talk back to chat. Failures are ignored,
+// since there's nothing better to do about a reply that didn't make it than
+// carry on.
+#[async_trait]
+pub trait Chat {
+  async fn say(&self, channel_id: u64, content: &str) -> ();
+
+  async fn reply(&self, msg: &ChatMessage, content: &str) -> ();
+
+  async fn react(&self, msg: &ChatMessage, emoji: &str) -> ();
+
+  // This gets called while the input stack is locked, so rather than waiting
+  // on the platform, updates should be queued up and made in order.
+  fn update_status(&self, update: StatusUpdate) -> ();
+
+  async fn set_activity(&self, activity: &str) -> ();
+
+  // The bot's own user ID, for platforms where mentioning it works as a
+  // prefix.
+  async fn bot_user_id(&self) -> Option<u64>;
+
+  // How to refer to a user in a reply, and how to work out who's being
+  // referred to in a command. By default, these go by Discord's mentions.
+  fn mention(&self, user_id: u64) -> String {
+    return format!("<@{}>", user_id);
+  }
+
+  fn parse_mention(&self, s: &str) -> Option<u64> {
+    return parse_user_mention(s);
+  }
+}
+
+// Platforms that only know users by name still need an ID for bans and stats,
+// so one's made from the name. This is FNV-1a, which unlike the standard
+// library's hasher is guaranteed to stay the same between builds. The top bit
+// is dropped, since TOML can only read back integers that fit in an i64.
+pub fn name_id(name: &str) -> u64 {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in name.to_lowercase().bytes() {
+    hash ^= byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  return hash & i64::MAX as u64;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::{ban_list::BanList, user_stats::StatsList};
+  use std::{env, fs, path::PathBuf, process};
+
+  #[test]
+  fn name_ids_ignore_case() {
+    assert_eq!(name_id("Console"), name_id("console"));
+    assert_ne!(name_id("alice"), name_id("bob"));
+  }
+
+  #[test]
+  fn name_ids_survive_being_saved() {
+    let dir: PathBuf = env::temp_dir().join(format!("crowdplay-name-id-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let bans_path: String = dir.join("bans.toml").to_str().unwrap().to_string();
+    let stats_path: String = dir.join("stats.toml").to_str().unwrap().to_string();
+    let user: u64 = name_id("console");
+
+    let mut bans: BanList = BanList::load(&bans_path).unwrap();
+    bans.ban(user, None);
+    bans.save().unwrap();
+    let mut stats: StatsList = StatsList::load(&stats_path).unwrap();
+    stats.record(user, "console", "up 3", 0);
+    stats.take_changes().unwrap().store().unwrap();
+
+    let bans: BanList = BanList::load(&bans_path).unwrap();
+    let stats: StatsList = StatsList::load(&stats_path).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    assert!(bans.is_banned(user, 0));
+    assert_eq!(*stats.get(user).unwrap().get_inputs(), 1);
+  }
+}
\ No newline at end of file
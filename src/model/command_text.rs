@@ -0,0 +1,76 @@
+// Chat messages are messy, so everything goes through here before it's parsed.
+// Commands are lowercased, stripped of punctuation and have their whitespace
+// collapsed, so "Shields,  2!" ends up as "shields 2". Hyphens and underscores
+// stay since they show up in negative numbers and aliases like "clone_bay".
+pub fn normalize(content: &str) -> String {
+  let stripped: String = content
+    .to_lowercase()
+    .chars()
+    .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-' || *c == '_')
+    .collect();
+  return stripped.split_whitespace().collect::<Vec<&str>>().join(" ");
+}
+
+// The Levenshtein distance between two words, or in other words, how many
+// characters need to be added, removed or swapped to turn one into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let a: Vec<char> = a.chars().collect();
+  let b: Vec<char> = b.chars().collect();
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+  for i in 1..=a.len() {
+    let mut curr: Vec<usize> = vec![i; b.len() + 1];
+    for j in 1..=b.len() {
+      let substitution: usize = match a[i - 1] == b[j - 1] {
+        true => prev[j - 1],
+        false => prev[j - 1] + 1
+      };
+      curr[j] = substitution.min(prev[j] + 1).min(curr[j - 1] + 1);
+    }
+    prev = curr;
+  }
+  return prev[b.len()];
+}
+
+// Picks the closest alias to an unknown command, as long as it's close enough
+// that it was probably a typo rather than something else entirely.
+pub fn suggest<'a>(word: &str, aliases: &Vec<&'a str>) -> Option<&'a str> {
+  // Single characters are too short to guess from.
+  let max_distance: usize = match word.chars().count() {
+    0..=1 => return None,
+    2..=4 => 1,
+    _ => 2
+  };
+  return aliases.iter()
+    .map(|alias| (*alias, edit_distance(word, alias)))
+    .filter(|(_, distance)| *distance <= max_distance)
+    .min_by_key(|(_, distance)| *distance)
+    .map(|(alias, _)| alias);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn normalizes_messy_commands() {
+    assert_eq!(normalize("Shields,  2!"), "shields 2");
+    assert_eq!(normalize("  Clone_Bay -1 "), "clone_bay -1");
+    assert_eq!(normalize("?!"), "");
+  }
+
+  #[test]
+  fn measures_edit_distance() {
+    assert_eq!(edit_distance("shields", "shields"), 0);
+    assert_eq!(edit_distance("sheilds", "shields"), 2);
+    assert_eq!(edit_distance("", "up"), 2);
+  }
+
+  #[test]
+  fn suggests_close_aliases() {
+    let aliases: Vec<&str> = vec!["shields", "engines", "up", "down"];
+    assert_eq!(suggest("shieds", &aliases), Some("shields"));
+    assert_eq!(suggest("dwn", &aliases), Some("down"));
+    assert_eq!(suggest("weapons", &aliases), None);
+    assert_eq!(suggest("u", &aliases), None);
+  }
+}
\ No newline at end of file
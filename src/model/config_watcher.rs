@@ -0,0 +1,65 @@
+use crate::model::{
+  app_model::AppModel,
+  audit::AuditLog,
+  error::ConfigError,
+  settings::BotSettings
+};
+use std::{fs, sync::Arc, time::SystemTime};
+
+// Loads the config from wherever it came from, including anything that was
+// overridden on the command line, so overrides survive a reload.
+pub type ConfigLoader =
+  Box<dyn Fn() -> Result<Box<dyn AppModel>, ConfigError> + Send + Sync>;
+
+// Keeps an eye on the config file's modification time. There's no fancy file
+// system notification going on here, whoever owns the watcher is expected to
+// poll it every so often.
+pub struct ConfigWatcher {
+  path: String,
+  loader: ConfigLoader,
+  // Whether the bot connects to chat, so that frontends that don't, like the
+  // console, aren't held to settings they never use, like the token.
+  connects: bool,
+  last_modified: Option<SystemTime>
+}
+
+impl ConfigWatcher {
+  pub fn new(path: String, loader: ConfigLoader, connects: bool) -> ConfigWatcher {
+    let last_modified: Option<SystemTime> = ConfigWatcher::modified(&path);
+    return ConfigWatcher {
+      path: path,
+      loader: loader,
+      connects: connects,
+      last_modified: last_modified
+    }
+  }
+
+  fn modified(path: &str) -> Option<SystemTime> {
+    return fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+  }
+
+  pub fn get_path(&self) -> &String {
+    return &self.path;
+  }
+
+  // Returns nothing if the file hasn't changed since the last poll. A missing
+  // file counts as unchanged, since editors tend to briefly delete files while
+  // saving them, and loading then would just write out a default config. The
+  // audit log carries over as is, since changing it takes a restart.
+  pub fn poll(&mut self, audit_log: Option<Arc<AuditLog>>) -> Option<Result<BotSettings, ConfigError>> {
+    let modified: Option<SystemTime> = ConfigWatcher::modified(&self.path);
+    if modified.is_none() || modified == self.last_modified {
+      return None;
+    }
+    self.last_modified = modified;
+
+    let cfg: Box<dyn AppModel> = match (self.loader)() {
+      Err(e) => return Some(Err(e)),
+      Ok(cfg) => cfg
+    };
+    if let Err(e) = cfg.check(self.connects) {
+      return Some(Err(e));
+    }
+    return Some(cfg.build_settings(audit_log));
+  }
+}
\ No newline at end of file
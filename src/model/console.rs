@@ -0,0 +1,203 @@
+use crate::model::{
+  bot::Bot,
+  chat::{Chat, ChatMessage, StatusUpdate, name_id},
+  dispatcher::Dispatcher,
+  error::PlatformError,
+  settings::BotSettings
+};
+use serenity::async_trait;
+use std::{
+  collections::HashMap,
+  fs,
+  io::Cursor,
+  sync::{Arc, Mutex},
+  time::Duration
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader, Lines};
+
+// Everything typed into the console comes from the same channel.
+const CONSOLE_CHANNEL: u64 = 1;
+
+// Lines look like "alice: up 3" to speak as someone else, or just "up 3" to
+// speak as whoever's running the console.
+fn split_speaker<'a>(line: &'a str, operator: &'a str) -> (&'a str, &'a str) {
+  if let Some((name, content)) = line.split_once(':') {
+    if !name.is_empty() && !name.contains(char::is_whitespace) {
+      return (name, content.trim_start());
+    }
+  }
+  return (operator, line);
+}
+
+// Prints whatever the bot would've said in chat. Messages are remembered so that
+// status changes can say which message they're for.
+struct ConsoleChat {
+  names: Mutex<HashMap<u64, String>>,
+  messages: Mutex<HashMap<u64, String>>
+}
+
+impl ConsoleChat {
+  fn new() -> ConsoleChat {
+    return ConsoleChat {
+      names: Mutex::new(HashMap::new()),
+      messages: Mutex::new(HashMap::new())
+    }
+  }
+
+  fn remember(&self, msg: &ChatMessage) -> () {
+    self.names.lock()
+      .expect("Expected the names' lock to not be poisoned.")
+      .insert(*msg.get_user_id(), msg.get_user().to_string());
+    self.messages.lock()
+      .expect("Expected the messages' lock to not be poisoned.")
+      .insert(*msg.get_id(), format!("{}: {}", msg.get_user(), msg.get_content()));
+  }
+}
+
+#[async_trait]
+impl Chat for ConsoleChat {
+  async fn say(&self, _channel_id: u64, content: &str) -> () {
+    println!("bot: {}", content);
+  }
+
+  async fn reply(&self, msg: &ChatMessage, content: &str) -> () {
+    println!("bot (to {}): {}", msg.get_user(), content);
+  }
+
+  async fn react(&self, msg: &ChatMessage, emoji: &str) -> () {
+    println!("{} {}: {}", emoji, msg.get_user(), msg.get_content());
+  }
+
+  fn update_status(&self, update: StatusUpdate) -> () {
+    let messages = self.messages.lock().expect("Expected the messages' lock to not be poisoned.");
+    let message: &str = messages.get(&update.message_id).map(|message| message.as_str()).unwrap_or("?");
+    println!("{} {}", update.status.emoji(), message);
+  }
+
+  async fn set_activity(&self, activity: &str) -> () {
+    println!("(status: {})", activity);
+  }
+
+  // There's no bot user to mention in the console.
+  async fn bot_user_id(&self) -> Option<u64> {
+    return None;
+  }
+
+  fn mention(&self, user_id: u64) -> String {
+    match self.names.lock().expect("Expected the names' lock to not be poisoned.").get(&user_id) {
+      None => return format!("user {}", user_id),
+      Some(name) => return format!("@{}", name)
+    }
+  }
+
+  // Users are mentioned by name, with or without an @ in front.
+  fn parse_mention(&self, s: &str) -> Option<u64> {
+    let name: &str = s.trim_start_matches('@');
+    match name.is_empty() {
+      true => return None,
+      false => return Some(name_id(name))
+    }
+  }
+}
+
+// Reads chat from stdin or a script instead of Discord, so the whole bot can be
+// tried out without a token or a network connection. Whoever's running the
+// console is always an admin.
+pub struct BotConsole {
+  dispatcher: Arc<Dispatcher>,
+  script: Option<String>,
+  operator: String
+}
+
+impl BotConsole {
+  pub fn new(dispatcher: Arc<Dispatcher>, script: Option<String>, operator: String) -> BotConsole {
+    return BotConsole {
+      dispatcher: dispatcher,
+      script: script,
+      operator: operator
+    }
+  }
+
+  fn read_lines(&self) -> Result<Lines<Box<dyn AsyncBufRead + Unpin + Send>>, PlatformError> {
+    let reader: Box<dyn AsyncBufRead + Unpin + Send> = match &self.script {
+      None => Box::new(BufReader::new(tokio::io::stdin())),
+      Some(path) => match fs::read_to_string(path) {
+        Err(e) => return Err(PlatformError::Script(path.to_string(), e)),
+        Ok(script) => Box::new(Cursor::new(script.into_bytes()))
+      }
+    };
+    return Ok(reader.lines());
+  }
+
+  fn to_message(&self, settings: &BotSettings, id: u64, line: &str) -> ChatMessage {
+    let (user, content): (&str, &str) = split_speaker(line, &self.operator);
+    let user_id: u64 = name_id(user);
+    let admin: bool = user == self.operator || settings.get_admins().is_admin(user_id, &Vec::new());
+    return ChatMessage::new(id, CONSOLE_CHANNEL, user_id, user.to_string(), admin, content.to_string());
+  }
+
+  // Whatever's still queued once the input runs out gets a chance to finish,
+  // unless it never would, like when it's paused or the game doesn't have focus
+  // and the focus loss policy holds onto inputs until it does.
+  async fn drain(&self) -> () {
+    loop {
+      let left: usize = self.dispatcher.get_input_stack().read().await.len();
+      let stuck: Option<&str> = match left {
+        0 => return,
+        _ if self.dispatcher.is_paused() => Some("inputs are paused"),
+        _ if !self.dispatcher.is_focused() => Some("the game doesn't have focus"),
+        _ => None
+      };
+      if let Some(reason) = stuck {
+        println!("Stopped with {} input(s) still queued, since {}.", left, reason);
+        return;
+      }
+      tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+  }
+}
+
+#[async_trait]
+impl Bot for BotConsole {
+  async fn start(&mut self) -> Result<(), PlatformError> {
+    let mut lines = self.read_lines()?;
+    let chat: Arc<ConsoleChat> = Arc::new(ConsoleChat::new());
+    self.dispatcher.serve();
+    self.dispatcher.start(Arc::clone(&chat) as Arc<dyn Chat + Send + Sync>);
+    chat.set_activity(&self.dispatcher.activity().await).await;
+
+    let mut id: u64 = 0;
+    loop {
+      let line: String = match lines.next_line().await {
+        Err(e) => return Err(PlatformError::Script(
+          self.script.clone().unwrap_or("stdin".to_string()),
+          e
+        )),
+        Ok(None) => break,
+        Ok(Some(line)) => line
+      };
+      let line: &str = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      // Scripts aren't shown as they're read like typed lines are, so they're
+      // echoed to make the output easier to follow.
+      if self.script.is_some() {
+        println!("> {}", line);
+      }
+
+      id += 1;
+      let settings: Arc<BotSettings> = self.dispatcher.current_settings().await;
+      let msg: ChatMessage = self.to_message(&settings, id, line);
+      chat.remember(&msg);
+      self.dispatcher.handle_message(&*chat, msg).await;
+    }
+
+    self.drain().await;
+    return Ok(());
+  }
+
+  async fn flush(&self) -> () {
+    self.dispatcher.flush().await;
+  }
+}
\ No newline at end of file
@@ -0,0 +1,796 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::{
+  admin::AdminCommand,
+  audit::AuditEntry,
+  ban_list::{BanList, parse_duration, unix_now},
+  bot::MessageParser,
+  chat::{Chat, ChatMessage, StatusUpdate},
+  command_text::{normalize, suggest},
+  config_watcher::ConfigWatcher,
+  error::ParseError,
+  execution_time::{ExecutionTimes, describe_wait},
+  input_status::InputStatus,
+  metrics::{Metrics, serve_metrics},
+  overlay::{Overlay, OverlaySource, serve_overlay},
+  queue::{QueuedInput, SharedInputStack},
+  settings::{BotSettings, SharedSettings},
+  user_stats::{StatsList, UserStats},
+  viewer::{QueueFeedback, ViewerCommand, position_emoji}
+};
+use hyper::server::{Builder, conn::AddrIncoming};
+use std::{
+  sync::{
+    Arc,
+    Mutex,
+    atomic::{
+      AtomicBool,
+      AtomicU64,
+      Ordering
+    }
+  },
+  str::FromStr,
+  time::{Duration, Instant}
+};
+use tokio::sync::{
+  RwLock,
+  RwLockReadGuard,
+  RwLockWriteGuard
+};
+use tracing::{Instrument, Span, debug, info, info_span, warn};
+
+// However many upcoming inputs the queue command lists.
+const SHOWN_QUEUE: usize = 10;
+
+// Only unknown commands get a suggestion. If the command is known, then it was
+// the arguments that were wrong, and the parser's own error explains those.
+fn suggest_command(msg_parser: &Box<dyn MessageParser + Send + Sync>, cmd: &str) -> Option<String> {
+  return suggest(cmd, &msg_parser.aliases()).map(|alias| alias.to_string());
+}
+
+fn describe_stats(stats: &UserStats) -> String {
+  let favorites: Vec<String> = stats.favorite_actions().iter()
+    .map(|(action, count)| format!("`{}` ({})", action, count))
+    .collect();
+  return format!(
+    "{} has had {} input(s) executed. Favorite actions: {}. Current streak: {} day(s), best: {} day(s).",
+    stats.get_name(),
+    stats.get_inputs(),
+    favorites.join(", "),
+    stats.current_streak(unix_now()),
+    stats.get_best_streak()
+  );
+}
+
+// Whatever the activity should say. This is shared between startup, reloads,
+// switching games and losing focus, since all of them can change what it says.
+pub fn activity_for(settings: &BotSettings, focused: bool) -> String {
+  match focused {
+    true => return format!(
+      "{} | prefix \"{}\"",
+      settings.get_game().to_string(),
+      settings.get_prefix().get_prefix()
+    ),
+    false => return format!(
+      "{} | waiting for focus",
+      settings.get_game().to_string()
+    )
+  }
+}
+
+// Swaps the queued status on an input's message for its new one.
+fn mark_input(chat: &(dyn Chat + Send + Sync), queued: &QueuedInput, status: InputStatus) -> () {
+  chat.update_status(StatusUpdate {
+    channel_id: *queued.get_channel_id(),
+    message_id: *queued.get_message_id(),
+    replaces: Some(InputStatus::Queued),
+    status: status
+  });
+}
+
+// Marks a message whose input never made it into the queue.
+fn mark_message(chat: &(dyn Chat + Send + Sync), msg: &ChatMessage, status: InputStatus) -> () {
+  chat.update_status(StatusUpdate {
+    channel_id: *msg.get_channel_id(),
+    message_id: *msg.get_id(),
+    replaces: None,
+    status: status
+  });
+}
+
+// Everything that happens between a chat message coming in and its input being
+// pressed, shared between every chat platform. Platforms only have to turn
+// their messages into ChatMessages, and give it a Chat to answer through.
+pub struct Dispatcher {
+  input_stack: SharedInputStack,
+  settings: SharedSettings,
+  bans: Arc<RwLock<BanList>>,
+  stats: Arc<RwLock<StatsList>>,
+  paused: Arc<AtomicBool>,
+  // Whether the game had focus the last time the input loop checked.
+  focused: Arc<AtomicBool>,
+  // The channel the last input came from, or 0 if there hasn't been one yet.
+  last_channel: Arc<AtomicU64>,
+  // Metrics are always kept track of, even if they're not being served.
+  metrics: Arc<Metrics>,
+  // How long each action usually takes, for estimating how long the queue will
+  // take to get through.
+  timings: Arc<ExecutionTimes>,
+  overlay: Arc<Overlay>,
+  // Platforms can connect more than once, like after a reconnect, but there
+  // should only ever be one input loop.
+  started: AtomicBool,
+  // Taken out once they're started.
+  watcher: Mutex<Option<ConfigWatcher>>,
+  metrics_server: Mutex<Option<Builder<AddrIncoming>>>,
+  overlay_server: Mutex<Option<Builder<AddrIncoming>>>
+}
+
+impl Dispatcher {
+  pub fn new(settings: BotSettings, ban_list: BanList, stats_list: StatsList, watcher: Option<ConfigWatcher>,
+    metrics_server: Option<Builder<AddrIncoming>>, overlay: Overlay,
+    overlay_server: Option<Builder<AddrIncoming>>) -> Dispatcher {
+    return Dispatcher {
+      input_stack: Arc::new(RwLock::new(Vec::new())),
+      settings: Arc::new(RwLock::new(Arc::new(settings))),
+      bans: Arc::new(RwLock::new(ban_list)),
+      stats: Arc::new(RwLock::new(stats_list)),
+      paused: Arc::new(AtomicBool::new(false)),
+      // Assumed until the input loop checks, so that a game that starts out
+      // without focus gets announced like any other focus loss.
+      focused: Arc::new(AtomicBool::new(true)),
+      last_channel: Arc::new(AtomicU64::new(0)),
+      metrics: Arc::new(Metrics::new()),
+      timings: Arc::new(ExecutionTimes::new()),
+      overlay: Arc::new(overlay),
+      started: AtomicBool::new(false),
+      watcher: Mutex::new(watcher),
+      metrics_server: Mutex::new(metrics_server),
+      overlay_server: Mutex::new(overlay_server)
+    }
+  }
+
+  // Grabs whatever settings are current right now. Holding onto these for the
+  // rest of an event is fine, since a reload only swaps in a new Arc.
+  pub async fn current_settings(&self) -> Arc<BotSettings> {
+    return self.settings.read().await.clone();
+  }
+
+  pub fn get_input_stack(&self) -> &SharedInputStack {
+    return &self.input_stack;
+  }
+
+  pub fn is_paused(&self) -> bool {
+    return self.paused.load(Ordering::Relaxed);
+  }
+
+  // As of the last time the input loop checked.
+  pub fn is_focused(&self) -> bool {
+    return self.focused.load(Ordering::Relaxed);
+  }
+
+  pub async fn activity(&self) -> String {
+    return activity_for(&*self.current_settings().await, self.focused.load(Ordering::Relaxed));
+  }
+
+  // Serves metrics and the overlay, if either of them were asked for. These
+  // don't need a connection to chat, so they can be started right away.
+  pub fn serve(&self) -> () {
+    let metrics_server: Option<Builder<AddrIncoming>> = self.metrics_server.lock()
+      .expect("Expected the metrics server's lock to not be poisoned.")
+      .take();
+    if let Some(server) = metrics_server {
+      tokio::spawn(serve_metrics(
+        server,
+        Arc::clone(&self.metrics),
+        Arc::clone(&self.input_stack),
+        Arc::clone(&self.paused)
+      ));
+    }
+    let overlay_server: Option<Builder<AddrIncoming>> = self.overlay_server.lock()
+      .expect("Expected the overlay server's lock to not be poisoned.")
+      .take();
+    if let Some(server) = overlay_server {
+      tokio::spawn(serve_overlay(server, OverlaySource {
+        overlay: Arc::clone(&self.overlay),
+        input_stack: Arc::clone(&self.input_stack),
+        settings: Arc::clone(&self.settings),
+        paused: Arc::clone(&self.paused),
+        focused: Arc::clone(&self.focused)
+      }));
+    }
+  }
+
+  // Starts the input loop, along with everything else that runs in the
+  // background, once the platform has a Chat to hand over.
+  pub fn start(self: &Arc<Self>, chat: Arc<dyn Chat + Send + Sync>) -> () {
+    if self.started.swap(true, Ordering::Relaxed) {
+      return;
+    }
+
+    let dispatcher: Arc<Dispatcher> = Arc::clone(self);
+    let loop_chat: Arc<dyn Chat + Send + Sync> = Arc::clone(&chat);
+    tokio::spawn(async move {
+      // When the game lost focus, if it doesn't have it right now.
+      let mut lost_at: Option<Instant> = None;
+      loop {
+        dispatcher.pop_input(&*loop_chat, &mut lost_at).await;
+        tokio::time::sleep(Duration::from_millis(1)).await;
+      }
+    });
+    tokio::spawn(Arc::clone(self).save_stats());
+
+    let watcher: Option<ConfigWatcher> = self.watcher.lock()
+      .expect("Expected the config watcher's lock to not be poisoned.")
+      .take();
+    if let Some(watcher) = watcher {
+      tokio::spawn(Arc::clone(self).watch_config(chat, watcher));
+    }
+  }
+
+  // A function representing a command for a generic input.
+  // In general, the idea is that it'll pass the message content to the parser,
+  // after which it's supposed to retrieve an input to add to the stack.
+  // The repeat policy is checked here rather than when popping, so that inputs
+  // that are way too long never get a chance to hog the stack.
+  // On success, this returns the input's position in the queue, along with
+  // about how long it'll be until it's executed if that can be estimated yet.
+  async fn push_input(&self, settings: &BotSettings, chat: &(dyn Chat + Send + Sync), msg: &ChatMessage,
+    content: &str) -> Result<(usize, Option<Duration>), ParseError> {
+    self.metrics.command_received();
+
+    // This enables us to write to the input stack without deadlocking the bot.
+    // The only problem is that this is obnoxiously ugly.
+    let mut input_stack:
+      RwLockWriteGuard<Vec<QueuedInput>> =
+      self.input_stack.write().await;
+    let command: String = normalize(content);
+    match settings.get_msg_parser().parse_msg(&command) {
+      Err(e) => {
+        let e: ParseError = settings.get_repeat_policy().explain(e);
+        self.metrics.command_rejected(&e);
+        return Err(e);
+      },
+      Ok(input) => match settings.get_repeat_policy().check(*input.get_presses()) {
+        Err(e) => {
+          self.metrics.command_rejected(&e);
+          return Err(e);
+        },
+        Ok(_) => {
+          self.metrics.input_queued();
+          input_stack.insert(0, QueuedInput::new(
+            input,
+            msg.get_content().to_string(),
+            command.to_string(),
+            *msg.get_user_id(),
+            msg.get_user().to_string(),
+            *msg.get_channel_id(),
+            *msg.get_id(),
+            Span::current()
+          ));
+          // This is sent while the stack's still locked, so it's always sent
+          // before anything the input loop does with the input.
+          mark_message(chat, msg, InputStatus::Queued);
+          self.drop_overflow(settings, chat, &mut input_stack);
+
+          // Everything else that's queued is ahead of this input.
+          let position: usize = input_stack.len();
+          let wait: Option<Duration> = self.timings.estimate(settings.get_game(), input_stack.iter().skip(1));
+          info!(command = %command, position = position, "Queued an input");
+          return Ok((position, wait));
+        }
+      }
+    }
+  }
+
+  // Makes room in a full queue by dropping the oldest input that hasn't started
+  // yet. The input that's partway through is left alone, since dropping it
+  // would cut it off in the middle.
+  fn drop_overflow(&self, settings: &BotSettings, chat: &(dyn Chat + Send + Sync),
+    input_stack: &mut Vec<QueuedInput>) -> () {
+    let max_queue: usize = *settings.get_max_queue();
+    if max_queue == 0 || input_stack.len() <= max_queue {
+      return;
+    }
+    if let Some(i) = input_stack.iter().rposition(|queued| queued.get_started_at().is_none()) {
+      let dropped: QueuedInput = input_stack.remove(i);
+      info!(parent: dropped.get_span(), command = %dropped.get_command(),
+        "Dropped an input, since the queue is full");
+      self.metrics.inputs_dropped("overflow", 1);
+      mark_input(chat, &dropped, InputStatus::Dropped);
+    }
+  }
+
+  // Checks the ban list, clearing out any bans that have expired along the way.
+  // Like the stats, the list is saved from a copy off of the runtime's threads,
+  // so that checking doesn't wait on the disk.
+  async fn is_banned(&self, user_id: u64) -> bool {
+    let now: u64 = unix_now();
+    let (banned, expired): (bool, Option<BanList>) = {
+      let mut ban_list: RwLockWriteGuard<BanList> = self.bans.write().await;
+      let expired: Option<BanList> = match ban_list.remove_expired(now) {
+        true => Some(ban_list.clone()),
+        false => None
+      };
+      (ban_list.is_banned(user_id, now), expired)
+    };
+
+    if let Some(ban_list) = expired {
+      let failed: Option<String> = match tokio::task::spawn_blocking(move || ban_list.save()).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(e) => Some(e.to_string())
+      };
+      if let Some(e) = failed {
+        warn!(error = %e, "Couldn't save the ban list after clearing out expired bans");
+      }
+    }
+    return banned;
+  }
+
+  async fn run_ban_command(&self, cmd: &AdminCommand, args: &Vec<&str>, chat: &(dyn Chat + Send + Sync)) -> String {
+    let user_id: u64 = match args.get(0).and_then(|arg| chat.parse_mention(arg)) {
+      None => return "Expected a user to be mentioned.".to_string(),
+      Some(user_id) => user_id
+    };
+    let mut ban_list: RwLockWriteGuard<BanList> = self.bans.write().await;
+
+    let reply: String = match cmd {
+      AdminCommand::Unban => match ban_list.unban(user_id) {
+        true => format!("Unbanned {}.", chat.mention(user_id)),
+        false => return format!("{} isn't banned.", chat.mention(user_id))
+      },
+      _ => match args.get(1) {
+        None => {
+          ban_list.ban(user_id, None);
+          format!("Banned {} until they're unbanned.", chat.mention(user_id))
+        },
+        Some(arg) => match parse_duration(arg) {
+          Err(_) => return "Ban durations look like \"30s\", \"10m\", \"2h\" or \"1d\".".to_string(),
+          // The ban file can't hold timestamps past i64's range, so anything
+          // that long might as well be permanent.
+          Ok(seconds) => match unix_now().checked_add(seconds).filter(|expires| *expires <= i64::MAX as u64) {
+            None => return "That's too long of a ban. Leave the duration out to ban them until they're unbanned.".to_string(),
+            Some(expires) => {
+              ban_list.ban(user_id, Some(expires));
+              format!("Banned {} for {}.", chat.mention(user_id), arg)
+            }
+          }
+        }
+      }
+    };
+
+    match ban_list.save() {
+      Err(e) => return format!("{} The ban list couldn't be saved though: {}", reply, e),
+      Ok(_) => return reply
+    }
+  }
+
+  // Lists the next few inputs in the order they'll be executed, which is the
+  // reverse of the stack's.
+  async fn describe_queue(&self, settings: &BotSettings) -> String {
+    let input_stack: RwLockReadGuard<Vec<QueuedInput>> = self.input_stack.read().await;
+
+    let mut lines: Vec<String> = vec![match self.is_paused() {
+      true => format!("{} input(s) queued, paused.", input_stack.len()),
+      false => format!("{} input(s) queued.", input_stack.len())
+    }];
+    if let Some(wait) = self.timings.estimate(settings.get_game(), input_stack.iter()) {
+      lines[0] = format!("{} About {} to get through all of them.", lines[0], describe_wait(wait));
+    }
+    for (i, queued) in input_stack.iter().rev().take(SHOWN_QUEUE).enumerate() {
+      lines.push(format!("{}. {}: `{}`", i + 1, queued.get_user(), queued.get_command()));
+    }
+    if input_stack.len() > SHOWN_QUEUE {
+      lines.push(format!("...and {} more.", input_stack.len() - SHOWN_QUEUE));
+    }
+    return lines.join("\n");
+  }
+
+  // Viewer commands can be used by anyone, so they only ever read. Names are
+  // used rather than mentions, so nobody gets pinged just for being on the
+  // leaderboard or in the queue.
+  async fn run_viewer_command(&self, cmd: &ViewerCommand, args: &Vec<&str>, settings: &BotSettings,
+    chat: &(dyn Chat + Send + Sync), msg: &ChatMessage) -> String {
+    match cmd {
+      ViewerCommand::Queue => return self.describe_queue(settings).await,
+      ViewerCommand::Stats => {
+        let user_id: u64 = match args.get(0) {
+          None => *msg.get_user_id(),
+          Some(arg) => match chat.parse_mention(arg) {
+            None => return "Expected a user to be mentioned.".to_string(),
+            Some(user_id) => user_id
+          }
+        };
+        match self.stats.read().await.get(user_id) {
+          None => return "No inputs have been executed for that user yet.".to_string(),
+          Some(stats) => return describe_stats(stats)
+        }
+      },
+      ViewerCommand::Leaderboard => {
+        let stats_list: RwLockReadGuard<StatsList> = self.stats.read().await;
+        let leaderboard: Vec<&UserStats> = stats_list.leaderboard();
+        if leaderboard.is_empty() {
+          return "Nobody's had an input executed yet.".to_string();
+        }
+        return leaderboard.iter()
+          .enumerate()
+          .map(|(i, stats)| format!("{}. {}: {} input(s)", i + 1, stats.get_name(), stats.get_inputs()))
+          .collect::<Vec<String>>()
+          .join("\n");
+      }
+    }
+  }
+
+  // Throws away every queued input, returning how many there were.
+  async fn clear_queue(&self, chat: &(dyn Chat + Send + Sync)) -> usize {
+    let mut input_stack:
+      RwLockWriteGuard<Vec<QueuedInput>> =
+      self.input_stack.write().await;
+    let cleared: usize = input_stack.len();
+    for queued in input_stack.drain(..) {
+      mark_input(chat, &queued, InputStatus::Dropped);
+    }
+    self.metrics.inputs_dropped("cleared", cleared);
+    return cleared;
+  }
+
+  // Rebuilds the current settings for another game. The queue is cleared along
+  // the way, since inputs meant for one game are just noise in another.
+  async fn run_game_command(&self, args: &Vec<&str>, chat: &(dyn Chat + Send + Sync)) -> String {
+    let game: SupportedGame = match args.get(0) {
+      None => return format!(
+        "The current game is {}.",
+        self.current_settings().await.get_game().to_string()
+      ),
+      Some(arg) => match SupportedGame::from_str(&arg.to_lowercase()) {
+        Err(e) => return e.to_string(),
+        Ok(game) => game
+      }
+    };
+
+    let activity: String = {
+      let mut settings: RwLockWriteGuard<Arc<BotSettings>> = self.settings.write().await;
+      let new_settings: BotSettings =
+        match settings.get_config().set_game(Some(game)).build_settings(settings.get_audit_log().clone()) {
+          Err(e) => return format!("Couldn't switch games: {}", e),
+          Ok(new_settings) => new_settings
+        };
+      let activity: String = activity_for(&new_settings, self.focused.load(Ordering::Relaxed));
+      *settings = Arc::new(new_settings);
+      activity
+    };
+
+    let cleared: usize = self.clear_queue(chat).await;
+    chat.set_activity(&activity).await;
+    info!(game = %game.to_string(), cleared = cleared, "Switched games");
+    return format!(
+      "Switched to {}, and cleared {} queued input(s).",
+      game.to_string(),
+      cleared
+    );
+  }
+
+  // Admin commands all work on the input stack, ban list or settings in some
+  // way, so they return a message describing what they did.
+  async fn run_admin_command(&self, cmd: &AdminCommand, args: &Vec<&str>, chat: &(dyn Chat + Send + Sync)) -> String {
+    match cmd {
+      AdminCommand::Pause => {
+        self.paused.store(true, Ordering::Relaxed);
+        return "Inputs are paused.".to_string();
+      },
+      AdminCommand::Resume => {
+        self.paused.store(false, Ordering::Relaxed);
+        return "Inputs are resumed.".to_string();
+      },
+      AdminCommand::Clear => {
+        return format!("Cleared {} queued input(s).", self.clear_queue(chat).await);
+      },
+      AdminCommand::Skip => {
+        // The next input is always at the end of the stack, even if it's
+        // partway through being executed.
+        match self.input_stack.write().await.pop() {
+          None => return "There's nothing to skip.".to_string(),
+          Some(queued) => {
+            mark_input(chat, &queued, InputStatus::Dropped);
+            self.metrics.inputs_dropped("skipped", 1);
+            return "Skipped the next input.".to_string();
+          }
+        }
+      },
+      AdminCommand::Ban | AdminCommand::Unban => {
+        return self.run_ban_command(cmd, args, chat).await;
+      },
+      AdminCommand::Game => {
+        return self.run_game_command(args, chat).await;
+      }
+    }
+  }
+
+  // Sends a status update to the status channel, or to wherever the last input
+  // came from if there isn't one.
+  async fn announce(&self, chat: &(dyn Chat + Send + Sync), settings: &BotSettings, status: &str) -> () {
+    let channel_id: u64 = match settings.get_status_channel() {
+      Some(channel_id) => *channel_id,
+      None => self.last_channel.load(Ordering::Relaxed)
+    };
+    if channel_id != 0 {
+      chat.say(channel_id, status).await;
+    }
+  }
+
+  // Keeps track of whether the game has focus, letting chat know whenever that
+  // changes so nobody's left wondering why their inputs aren't happening.
+  // Queued inputs are dropped here too, if the focus loss policy says so.
+  async fn check_focus(&self, chat: &(dyn Chat + Send + Sync), settings: &BotSettings,
+    lost_at: &mut Option<Instant>) -> bool {
+    let focused: bool = settings.get_focus_checker().game_focused();
+    let was_focused: bool = self.focused.swap(focused, Ordering::Relaxed);
+
+    if focused != was_focused {
+      let status: String = match focused {
+        true => {
+          *lost_at = None;
+          "The game has focus again, so inputs are back on.".to_string()
+        },
+        false => {
+          *lost_at = Some(Instant::now());
+          settings.get_focus_loss().describe()
+        }
+      };
+      match focused {
+        true => info!("The game has focus again"),
+        false => info!("The game lost focus")
+      }
+      chat.set_activity(&activity_for(settings, focused)).await;
+      self.announce(chat, settings, &status).await;
+    }
+
+    if let Some(lost_at) = lost_at {
+      if settings.get_focus_loss().should_drop(lost_at.elapsed()) {
+        let mut input_stack: RwLockWriteGuard<Vec<QueuedInput>> = self.input_stack.write().await;
+        self.metrics.inputs_dropped("focus", input_stack.len());
+        for queued in input_stack.drain(..) {
+          info!(parent: queued.get_span(), command = %queued.get_command(),
+            "Dropped an input, since the game doesn't have focus");
+          mark_input(chat, &queued, InputStatus::Dropped);
+        }
+      }
+    }
+    return focused;
+  }
+
+  async fn pop_input(&self, chat: &(dyn Chat + Send + Sync), lost_at: &mut Option<Instant>) -> () {
+    if self.is_paused() {
+      return;
+    }
+
+    let settings: Arc<BotSettings> = self.current_settings().await;
+    if !self.check_focus(chat, &settings, lost_at).await {
+      return;
+    }
+
+    let mut input_stack:
+      RwLockWriteGuard<Vec<QueuedInput>> =
+      self.input_stack.write().await;
+
+    // I enjoy pattern matching in this language though.
+    let mut queued: QueuedInput = match input_stack.pop() {
+      None => return,
+      Some(queued) => queued
+    };
+    queued.start();
+    let pressed = queued.get_span().in_scope(|| queued.get_input().pop(&**settings.get_backend()));
+    match pressed {
+      // Inputs that can't be pressed are dropped, since trying again
+      // wouldn't go any differently.
+      Err(e) => {
+        warn!(parent: queued.get_span(), command = %queued.get_command(), error = %e,
+          "Dropped an input");
+        self.metrics.inputs_dropped("error", 1);
+        mark_input(chat, &queued, InputStatus::Dropped);
+      },
+      Ok(None) => {
+        info!(parent: queued.get_span(), command = %queued.get_command(), "Executed an input");
+        self.metrics.input_executed(
+          settings.get_game(),
+          queued.get_command(),
+          queued.get_queued_at().elapsed()
+        );
+        if let Some(started_at) = queued.get_started_at() {
+          self.timings.record(
+            settings.get_game(),
+            queued.get_command(),
+            queued.get_presses().unsigned_abs(),
+            started_at.elapsed()
+          );
+        }
+        self.overlay.executed(&queued);
+        mark_input(chat, &queued, InputStatus::Executed);
+        // Nothing from here on needs the stack, so everyone waiting on it can
+        // get going.
+        drop(input_stack);
+        if let Some(audit_log) = settings.get_audit_log() {
+          audit_log.append(AuditEntry::new(*settings.get_game(), &queued));
+        }
+        self.stats.write().await.record(
+          *queued.get_user_id(),
+          queued.get_user(),
+          queued.get_command(),
+          unix_now()
+        );
+      },
+      Ok(Some(next)) => {
+        debug!(parent: queued.get_span(), command = %queued.get_command(),
+          presses_left = *next.get_presses(), "Pressed an input");
+        input_stack.push(queued.with_input(next));
+      }
+    }
+  }
+
+  // Stats change with nearly every input, so rather than saving the whole list
+  // each time, it's saved every so often if anything's changed.
+  async fn save_stats(self: Arc<Self>) -> () {
+    loop {
+      tokio::time::sleep(Duration::from_secs(10)).await;
+      self.flush_stats().await;
+    }
+  }
+
+  // Makes sure everything's on disk, for when the bot's about to exit.
+  pub async fn flush(&self) -> () {
+    self.flush_stats().await;
+    let settings: Arc<BotSettings> = self.current_settings().await;
+    let _ = tokio::task::spawn_blocking(move || {
+      if let Some(audit_log) = settings.get_audit_log() {
+        audit_log.flush();
+      }
+    }).await;
+  }
+
+  // Saving touches the disk, so it's done off of the runtime's threads, from
+  // a copy so that the list doesn't stay locked the whole time.
+  async fn flush_stats(&self) -> () {
+    let changes: StatsList = match self.stats.write().await.take_changes() {
+      None => return,
+      Some(changes) => changes
+    };
+    let failed: Option<String> = match tokio::task::spawn_blocking(move || changes.store()).await {
+      Ok(Ok(_)) => None,
+      Ok(Err(e)) => Some(e.to_string()),
+      Err(e) => Some(e.to_string())
+    };
+    if let Some(e) = failed {
+      self.stats.write().await.mark_changed();
+      warn!(error = %e, "Couldn't save the stats list");
+    }
+  }
+
+  // Polls the config file for changes, swapping in new settings whenever it
+  // changes. Broken configs are reported and ignored, so a typo made
+  // mid-stream doesn't take the bot down with it.
+  async fn watch_config(self: Arc<Self>, chat: Arc<dyn Chat + Send + Sync>, mut watcher: ConfigWatcher) -> () {
+    loop {
+      tokio::time::sleep(Duration::from_secs(2)).await;
+      match watcher.poll(self.current_settings().await.get_audit_log().clone()) {
+        None => (),
+        Some(Err(e)) => warn!(
+          path = %watcher.get_path(),
+          error = %e,
+          "Kept the old settings, since the config couldn't be reloaded"
+        ),
+        Some(Ok(settings)) => {
+          let activity: String = activity_for(&settings, self.focused.load(Ordering::Relaxed));
+          let game: SupportedGame = *settings.get_game();
+          let old_game: SupportedGame = {
+            let mut current: RwLockWriteGuard<Arc<BotSettings>> = self.settings.write().await;
+            let old_game: SupportedGame = *current.get_game();
+            *current = Arc::new(settings);
+            old_game
+          };
+          // Same as switching games with the admin command, inputs queued for
+          // the old game would only press the wrong keys in the new one.
+          if game != old_game {
+            let cleared: usize = self.clear_queue(&*chat).await;
+            info!(game = %game.to_string(), cleared = cleared, "Switched games");
+          }
+          chat.set_activity(&activity).await;
+          info!(path = %watcher.get_path(), "Reloaded settings");
+        }
+      }
+    }
+  }
+
+  // Lets whoever sent an input know where it landed in the queue, if the
+  // config asks for it.
+  async fn give_queue_feedback(&self, settings: &BotSettings, chat: &(dyn Chat + Send + Sync), msg: &ChatMessage,
+    position: usize, wait: Option<Duration>) -> () {
+    match settings.get_queue_feedback() {
+      QueueFeedback::Off => (),
+      QueueFeedback::Reaction => {
+        if let Some(emoji) = position_emoji(position) {
+          chat.react(msg, emoji).await;
+        }
+      },
+      QueueFeedback::Reply => {
+        let reply: String = match wait {
+          None => format!("You're #{} in the queue.", position),
+          Some(wait) => format!("You're #{} in the queue, about {} to go.", position, describe_wait(wait))
+        };
+        chat.reply(msg, &reply).await;
+      }
+    }
+  }
+
+  // Everything that happens to a message, from reading it to queueing it.
+  // Everything logged about a message can be traced back to it, and to whoever
+  // sent it.
+  pub async fn handle_message(&self, chat: &(dyn Chat + Send + Sync), msg: ChatMessage) -> () {
+    let span: Span = info_span!(
+      "message",
+      message_id = *msg.get_id(),
+      user_id = *msg.get_user_id(),
+      user = %msg.get_user()
+    );
+    self.dispatch(chat, msg).instrument(span).await;
+  }
+
+  async fn dispatch(&self, chat: &(dyn Chat + Send + Sync), msg: ChatMessage) -> () {
+    let settings: Arc<BotSettings> = self.current_settings().await;
+    let bot_id: Option<u64> = chat.bot_user_id().await;
+    if let Some(slice) = settings.get_prefix().strip(msg.get_content(), *msg.get_channel_id(), bot_id) {
+      debug!(content = %slice, "Received a command");
+
+      // Admin commands take priority over game inputs.
+      let mut words: Vec<&str> = slice.split_whitespace().collect();
+      // Only the name's cleaned up, since arguments like mentions need their
+      // punctuation.
+      let name: String = normalize(words.remove(0));
+      if let Ok(cmd) = AdminCommand::from_str(&name) {
+        if msg.is_admin() {
+          let reply: String = self.run_admin_command(&cmd, &words, chat).await;
+          info!(command = %slice, reply = %reply, "Ran an admin command");
+          chat.say(*msg.get_channel_id(), &reply).await;
+        } else {
+          info!(command = %slice, "Refused an admin command from a non-admin");
+          chat.react(&msg, "🚫").await;
+        }
+        return;
+      }
+
+      if self.is_banned(*msg.get_user_id()).await {
+        info!("Ignored a command from a banned user");
+        return;
+      }
+
+      if let Ok(cmd) = ViewerCommand::from_str(&name) {
+        let reply: String = self.run_viewer_command(&cmd, &words, &settings, chat, &msg).await;
+        debug!(command = %slice, "Ran a viewer command");
+        chat.say(*msg.get_channel_id(), &reply).await;
+        return;
+      }
+
+      match self.push_input(&settings, chat, &msg, &slice).await {
+        Err(ParseError::UnknownCommand(cmd)) => {
+          let suggestion: Option<String> = suggest_command(settings.get_msg_parser(), &cmd);
+          info!(command = %cmd, suggestion = ?suggestion, "Rejected an unknown command");
+          // Unknown commands without a suggestion are most likely just chat,
+          // especially in prefix-less channels, so they're left alone.
+          if let Some(alias) = suggestion {
+            mark_message(chat, &msg, InputStatus::Rejected);
+            chat.reply(&msg, &format!("Did you mean `{}`?", alias)).await;
+          }
+        },
+        Err(ParseError::Empty) => (),
+        Err(e) => {
+          info!(error = %e, "Rejected a command");
+          mark_message(chat, &msg, InputStatus::Rejected);
+          chat.reply(&msg, &e.to_string()).await;
+        },
+        Ok((position, wait)) => {
+          self.last_channel.store(*msg.get_channel_id(), Ordering::Relaxed);
+          self.give_queue_feedback(&settings, chat, &msg, position, wait).await;
+        }
+      }
+    }
+  }
+}
\ No newline at end of file
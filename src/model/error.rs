@@ -0,0 +1,234 @@
+use confy::ConfyError;
+use std::{error::Error, fmt, io};
+
+// A single problem with one of the config's fields, along with a hint on how
+// to fix it.
+#[derive(Debug)]
+pub struct ConfigIssue {
+  field: String,
+  problem: String,
+  hint: String
+}
+
+impl ConfigIssue {
+  pub fn new(field: &str, problem: &str, hint: &str) -> ConfigIssue {
+    return ConfigIssue {
+      field: field.to_string(),
+      problem: problem.to_string(),
+      hint: hint.to_string()
+    }
+  }
+}
+
+impl fmt::Display for ConfigIssue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "`{}`: {} {}", self.field, self.problem, self.hint)
+  }
+}
+
+// Everything that can go wrong before the bot even connects, usually because
+// of something in the config file.
+#[derive(Debug)]
+pub enum ConfigError {
+  Load(String, ConfyError),
+  MissingGame,
+  UnsupportedGame(String),
+  MissingToken,
+  MissingTokenEnv(String),
+  TokenFile(String, io::Error),
+  AlreadyExists(String),
+  Write(String, io::Error),
+  InvalidPrefixPattern(String, regex::Error),
+  InvalidFocusRule(String, String),
+  BanList(String, ConfyError),
+  StatsFile(String, ConfyError),
+  LogFile(String, io::Error),
+  AuditFile(String, io::Error),
+  MetricsAddress(String, String),
+  OverlayAddress(String, String),
+  Invalid(Vec<ConfigIssue>)
+}
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ConfigError::Load(path, e) => write!(f,
+        "Couldn't load the config from \"{}\": {}", path, e),
+      ConfigError::MissingGame => write!(f,
+        "No game is set. Set `game` to either \"FTL\" or \"NDS\"."),
+      ConfigError::UnsupportedGame(game) => write!(f,
+        "{} isn't supported yet.", game),
+      ConfigError::MissingToken => write!(f,
+        "No OAuth token is set. Set `token_env`, `token_file` or `token` to give the bot its token."),
+      ConfigError::MissingTokenEnv(var) => write!(f,
+        "The environment variable \"{}\" isn't set, so there's no token to use.", var),
+      ConfigError::TokenFile(path, e) => write!(f,
+        "Couldn't read the token from \"{}\": {}", path, e),
+      ConfigError::AlreadyExists(path) => write!(f,
+        "\"{}\" already exists. Delete it first if you want a fresh one.", path),
+      ConfigError::Write(path, e) => write!(f,
+        "Couldn't write to \"{}\": {}", path, e),
+      ConfigError::InvalidPrefixPattern(pattern, e) => write!(f,
+        "`prefix_pattern` \"{}\" isn't a valid regular expression: {}", pattern, e),
+      ConfigError::InvalidFocusRule(game, reason) => write!(f,
+        "The focus rule for {} isn't usable: {}", game, reason),
+      ConfigError::BanList(path, e) => write!(f,
+        "Couldn't load the ban list from \"{}\": {}", path, e),
+      ConfigError::StatsFile(path, e) => write!(f,
+        "Couldn't load the stats from \"{}\": {}", path, e),
+      ConfigError::LogFile(path, e) => write!(f,
+        "Couldn't open the log file \"{}\": {}", path, e),
+      ConfigError::AuditFile(path, e) => write!(f,
+        "Couldn't open the audit log \"{}\": {}", path, e),
+      ConfigError::MetricsAddress(address, e) => write!(f,
+        "Couldn't serve metrics on {}: {}", address, e),
+      ConfigError::OverlayAddress(address, e) => write!(f,
+        "Couldn't serve the overlay on {}: {}", address, e),
+      ConfigError::Invalid(issues) => {
+        write!(f, "Found {} problem(s) with the config:", issues.len())?;
+        for issue in issues {
+          write!(f, "\n  {}", issue)?;
+        }
+        return Ok(());
+      }
+    }
+  }
+}
+
+impl Error for ConfigError {}
+
+// Why a chat message couldn't be turned into an input. These get shown to
+// whoever sent the message, so they should read like advice.
+#[derive(Debug)]
+pub enum ParseError {
+  Empty,
+  UnknownCommand(String),
+  UnknownGame(String),
+  InvalidArgument(String),
+  RepeatLimit(i8, i8, i64),
+  // A repeat count too big for an input to hold, which the repeat policy
+  // turns into a RepeatLimit once it's known what the limits are.
+  RepeatCount(i64)
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ParseError::Empty => write!(f, "There's no command to parse."),
+      ParseError::UnknownCommand(cmd) => write!(f,
+        "\"{}\" isn't a command.", cmd),
+      ParseError::UnknownGame(game) => write!(f,
+        "\"{}\" isn't a supported game. Try \"ftl\" or \"nds\".", game),
+      ParseError::InvalidArgument(reason) => write!(f, "{}", reason),
+      ParseError::RepeatLimit(min, max, presses) => write!(f,
+        "Repeat counts have to be between {} and {}, but got {}.", min, max, presses),
+      ParseError::RepeatCount(presses) => write!(f,
+        "{} is way too many repeats.", presses)
+    }
+  }
+}
+
+impl Error for ParseError {}
+
+// Problems with reading or replaying an audit log.
+#[derive(Debug)]
+pub enum AuditError {
+  Read(String, io::Error),
+  // The line number of an entry that couldn't be read.
+  Entry(String, usize),
+  // How many entries resolved to different keys, out of how many there are.
+  Mismatch(usize, usize)
+}
+
+impl fmt::Display for AuditError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AuditError::Read(path, e) => write!(f,
+        "Couldn't read \"{}\": {}", path, e),
+      AuditError::Entry(path, line) => write!(f,
+        "Line {} of \"{}\" isn't a valid entry.", line, path),
+      AuditError::Mismatch(mismatches, total) => write!(f,
+        "{} of {} input(s) didn't replay the same way they were logged.", mismatches, total)
+    }
+  }
+}
+
+impl Error for AuditError {}
+
+// Problems with the chat platform itself, like failing to log in.
+#[derive(Debug)]
+pub enum PlatformError {
+  Client(String),
+  Connection(String),
+  // The console couldn't read its script, or stdin.
+  Script(String, io::Error)
+}
+
+impl fmt::Display for PlatformError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PlatformError::Client(e) => write!(f, "Couldn't create the client: {}", e),
+      PlatformError::Connection(e) => write!(f, "Lost the connection: {}", e),
+      PlatformError::Script(path, e) => write!(f, "Couldn't read \"{}\": {}", path, e)
+    }
+  }
+}
+
+impl Error for PlatformError {}
+
+// Problems with actually pressing keys.
+#[derive(Debug)]
+pub enum InputError {
+  InvalidDelay(i16)
+}
+
+impl fmt::Display for InputError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InputError::InvalidDelay(delay) => write!(f,
+        "Key delays can't be negative, but got {}ms.", delay)
+    }
+  }
+}
+
+impl Error for InputError {}
+
+// What eventually makes its way back up to main.
+#[derive(Debug)]
+pub enum BotError {
+  Usage(String),
+  Config(ConfigError),
+  Platform(PlatformError),
+  Audit(AuditError)
+}
+
+impl fmt::Display for BotError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BotError::Usage(e) => write!(f, "{}", e),
+      BotError::Config(e) => write!(f, "Config error: {}", e),
+      BotError::Platform(e) => write!(f, "Platform error: {}", e),
+      BotError::Audit(e) => write!(f, "Audit log error: {}", e)
+    }
+  }
+}
+
+impl Error for BotError {}
+
+impl From<ConfigError> for BotError {
+  fn from(e: ConfigError) -> BotError {
+    return BotError::Config(e);
+  }
+}
+
+impl From<PlatformError> for BotError {
+  fn from(e: PlatformError) -> BotError {
+    return BotError::Platform(e);
+  }
+}
+
+impl From<AuditError> for BotError {
+  fn from(e: AuditError) -> BotError {
+    return BotError::Audit(e);
+  }
+}
\ No newline at end of file
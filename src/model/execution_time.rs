@@ -0,0 +1,75 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::queue::QueuedInput;
+use std::{
+  collections::HashMap,
+  sync::Mutex,
+  time::Duration
+};
+
+// How much each new measurement counts for, compared to everything measured
+// before it. Higher reacts to changes faster, but jumps around more.
+const SMOOTHING: f64 = 0.2;
+
+// Only the command's name counts as the action, so "up 3" and "up" share a
+// time.
+fn action_of(command: &str) -> String {
+  return command.split_whitespace().next().unwrap_or("").to_string();
+}
+
+// How long each action takes per press, going by how long they've taken since
+// the bot started. These are only ever estimates, since they can't know about
+// pauses or the game losing focus ahead of time.
+pub struct ExecutionTimes {
+  per_press: Mutex<HashMap<(&'static str, String), f64>>
+}
+
+impl ExecutionTimes {
+  pub fn new() -> ExecutionTimes {
+    return ExecutionTimes {
+      per_press: Mutex::new(HashMap::new())
+    }
+  }
+
+  pub fn record(&self, game: &SupportedGame, command: &str, presses: u8, elapsed: Duration) -> () {
+    if presses == 0 {
+      return;
+    }
+    let seconds: f64 = elapsed.as_secs_f64() / presses as f64;
+    let mut per_press = self.per_press.lock().expect("Expected the execution times' lock to not be poisoned.");
+    per_press.entry((game.to_id(), action_of(command)))
+      .and_modify(|average| *average += SMOOTHING * (seconds - *average))
+      .or_insert(seconds);
+  }
+
+  // How long it'll take to get through every input given, in seconds. Actions
+  // that haven't been timed yet are assumed to take as long as the game's
+  // actions usually do, and nothing can be estimated until at least one has
+  // been timed.
+  pub fn estimate<'a>(&self, game: &SupportedGame, inputs: impl Iterator<Item = &'a QueuedInput>) -> Option<Duration> {
+    let per_press = self.per_press.lock().expect("Expected the execution times' lock to not be poisoned.");
+    let known: Vec<f64> = per_press.iter()
+      .filter(|((timed_game, _), _)| *timed_game == game.to_id())
+      .map(|(_, seconds)| *seconds)
+      .collect();
+    if known.is_empty() {
+      return None;
+    }
+    let fallback: f64 = known.iter().sum::<f64>() / known.len() as f64;
+
+    let seconds: f64 = inputs.map(|queued| {
+      let seconds: f64 = *per_press.get(&(game.to_id(), action_of(queued.get_command()))).unwrap_or(&fallback);
+      return seconds * queued.get_presses_left() as f64;
+    }).sum();
+    return Some(Duration::from_secs_f64(seconds));
+  }
+}
+
+// Rounded to something that reads well in chat, like "45s" or "3m 20s".
+pub fn describe_wait(wait: Duration) -> String {
+  let seconds: u64 = wait.as_secs_f64().round() as u64;
+  match seconds {
+    0 => return "a moment".to_string(),
+    s if s < 60 => return format!("{}s", s),
+    s => return format!("{}m {}s", s / 60, s % 60)
+  }
+}
\ No newline at end of file
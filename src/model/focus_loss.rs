@@ -0,0 +1,54 @@
+use serde::{Serialize, Deserialize};
+use std::time::Duration;
+
+// What happens to queued inputs while the game doesn't have focus.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum FocusLossPolicy {
+  // Keep everything queued until focus comes back.
+  Hold,
+  // Throw away everything that's queued, and anything that comes in, until
+  // focus comes back.
+  Drop,
+  // Hold inputs at first, but start dropping them if focus has been gone for
+  // too long.
+  DropAfter
+}
+
+// The policy, along with how long DropAfter waits before dropping.
+pub struct FocusLoss {
+  policy: FocusLossPolicy,
+  timeout: Duration
+}
+
+impl FocusLoss {
+  pub fn new(policy: FocusLossPolicy, timeout_secs: u64) -> FocusLoss {
+    return FocusLoss {
+      policy: policy,
+      timeout: Duration::from_secs(timeout_secs)
+    }
+  }
+
+  // Whether queued inputs should be dropped, given how long the game has been
+  // without focus.
+  pub fn should_drop(&self, lost_for: Duration) -> bool {
+    match self.policy {
+      FocusLossPolicy::Hold => return false,
+      FocusLossPolicy::Drop => return true,
+      FocusLossPolicy::DropAfter => return lost_for >= self.timeout
+    }
+  }
+
+  // Tells chat what's about to happen to their inputs.
+  pub fn describe(&self) -> String {
+    match self.policy {
+      FocusLossPolicy::Hold => return
+        "The game lost focus, so inputs are on hold until it's back.".to_string(),
+      FocusLossPolicy::Drop => return
+        "The game lost focus, so inputs are being dropped until it's back.".to_string(),
+      FocusLossPolicy::DropAfter => return format!(
+        "The game lost focus, so inputs are on hold. They'll be dropped if it isn't back within {} second(s).",
+        self.timeout.as_secs()
+      )
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::bot::GameFocusChecker;
+use crate::model::window::Window;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::sync::Arc;
+
+// What part of the game's window gets compared against a rule's value.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum FocusMatch {
+  // The window title has to be exactly the value.
+  Exact,
+  // The window title has to contain the value somewhere.
+  Substring,
+  // The window title has to match the value as a regular expression.
+  Regex,
+  // The window has to belong to an executable with this file name, like
+  // "FTLGame.exe". Case doesn't matter.
+  Executable,
+  // The window has to belong to the process with this ID.
+  Pid,
+  // Don't check at all, and assume the game always has focus.
+  Always
+}
+
+// How to pick out the game's window, whether that's to tell if it has focus or
+// to find where to send inputs. Emulators and translated games don't always
+// have the window title the game expects, so this is configurable per game.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FocusRule {
+  kind: FocusMatch,
+  // Unused by "Always".
+  #[serde(default)]
+  value: String
+}
+
+impl FocusRule {
+  pub fn new(kind: FocusMatch, value: &str) -> FocusRule {
+    return FocusRule {
+      kind: kind,
+      value: value.to_string()
+    }
+  }
+
+  // Turns the rule into something that can actually match windows, or
+  // explains why the value doesn't make sense for the kind of rule.
+  pub fn to_matcher(&self) -> Result<WindowMatcher, String> {
+    let needs_value: bool = match self.kind {
+      FocusMatch::Always => false,
+      _ => true
+    };
+    if needs_value && self.value.is_empty() {
+      return Err("The rule has no `value` to match against.".to_string());
+    }
+    match self.kind {
+      FocusMatch::Exact => return Ok(WindowMatcher::Exact(self.value.to_string())),
+      FocusMatch::Substring => return Ok(WindowMatcher::Substring(self.value.to_string())),
+      FocusMatch::Regex => match Regex::new(&self.value) {
+        Err(e) => return Err(format!("\"{}\" isn't a valid regular expression: {}", self.value, e)),
+        Ok(regex) => return Ok(WindowMatcher::Regex(regex))
+      },
+      FocusMatch::Executable => return Ok(WindowMatcher::Executable(self.value.to_string())),
+      FocusMatch::Pid => match self.value.parse::<u32>() {
+        Err(_) => return Err(format!("\"{}\" isn't a process ID.", self.value)),
+        Ok(pid) => return Ok(WindowMatcher::Pid(pid))
+      },
+      FocusMatch::Always => return Ok(WindowMatcher::Always)
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusRules {
+  ftl: FocusRule,
+  nds: FocusRule
+}
+
+impl FocusRules {
+  pub fn for_game(&self, game: &SupportedGame) -> &FocusRule {
+    match game {
+      SupportedGame::FTL => return &self.ftl,
+      SupportedGame::NDS => return &self.nds
+    }
+  }
+}
+
+impl Default for FocusRules {
+  fn default() -> FocusRules {
+    return FocusRules {
+      ftl: FocusRule::new(FocusMatch::Exact, "FTL: Faster Than Light"),
+      // DeSmuME puts the ROM's name and its own version in the title too.
+      nds: FocusRule::new(FocusMatch::Substring, "DeSmuME")
+    }
+  }
+}
+
+pub enum WindowMatcher {
+  Exact(String),
+  Substring(String),
+  Regex(Regex),
+  Executable(String),
+  Pid(u32),
+  Always
+}
+
+impl WindowMatcher {
+  pub fn matches(&self, window: &Window) -> bool {
+    match self {
+      WindowMatcher::Exact(value) => return window.title()
+        .map_or(false, |title| title == *value),
+      WindowMatcher::Substring(value) => return window.title()
+        .map_or(false, |title| title.contains(value.as_str())),
+      WindowMatcher::Regex(regex) => return window.title()
+        .map_or(false, |title| regex.is_match(&title)),
+      WindowMatcher::Executable(name) => return window.executable()
+        .map_or(false, |file| file.eq_ignore_ascii_case(name)),
+      WindowMatcher::Pid(pid) => return window.pid() == Some(*pid),
+      WindowMatcher::Always => return true
+    }
+  }
+
+  // Whether the matcher could ever pick out a single window. "Always" matches
+  // everything, so it's only good for checking focus.
+  pub fn is_specific(&self) -> bool {
+    match self {
+      WindowMatcher::Always => return false,
+      _ => return true
+    }
+  }
+}
+
+// Checks focus using the game's focus rule. When inputs are sent straight to
+// the game's window, focus doesn't matter, so the window only has to exist.
+pub struct RuleFocusChecker {
+  matcher: Arc<WindowMatcher>,
+  targeted: bool
+}
+
+impl RuleFocusChecker {
+  pub fn new(matcher: Arc<WindowMatcher>, targeted: bool) -> RuleFocusChecker {
+    return RuleFocusChecker {
+      matcher: matcher,
+      targeted: targeted
+    }
+  }
+}
+
+impl GameFocusChecker for RuleFocusChecker {
+  fn game_focused(&self) -> bool {
+    // There might not be any window to check at all, like when trying the bot
+    // out from the console on a machine without a display.
+    if let WindowMatcher::Always = *self.matcher {
+      return true;
+    }
+    match self.targeted {
+      true => return Window::find(|window| self.matcher.matches(window)).is_some(),
+      false => return Window::focused().map_or(false, |window| self.matcher.matches(&window))
+    }
+  }
+}
\ No newline at end of file
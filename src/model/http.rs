@@ -0,0 +1,23 @@
+use hyper::{
+  Body,
+  Response,
+  Server,
+  StatusCode,
+  server::{Builder, conn::AddrIncoming}
+};
+use std::net::SocketAddr;
+
+// Servers are bound while the bot is being built, so that a port that's already
+// taken is reported before connecting, and only served once the bot starts.
+pub fn bind(address: &str) -> Result<Builder<AddrIncoming>, String> {
+  let address: SocketAddr = address.parse()
+    .map_err(|_| format!("\"{}\" isn't an address like \"127.0.0.1:9100\".", address))?;
+  return Server::try_bind(&address).map_err(|e| e.to_string());
+}
+
+pub fn not_found() -> Response<Body> {
+  return Response::builder()
+    .status(StatusCode::NOT_FOUND)
+    .body(Body::empty())
+    .expect("Expected the not found response to be valid.");
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+use crate::model::{focus_rule::WindowMatcher, window::Window};
+use inputbot::KeybdKey;
+use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex};
+
+// Whatever actually delivers key presses to the game. Inputs don't press keys
+// themselves, so that the same input can be sent somewhere else entirely, or
+// nowhere at all.
+pub trait InputBackend {
+  fn press(&self, key: KeybdKey) -> ();
+
+  fn release(&self, key: KeybdKey) -> ();
+}
+
+// Which backend the config asked for.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum InputBackendKind {
+  Global,
+  Targeted,
+  DryRun
+}
+
+impl InputBackendKind {
+  // The matcher is only used by the targeted backend, to find the game's
+  // window.
+  pub fn to_backend(&self, matcher: Arc<WindowMatcher>) -> Box<dyn InputBackend + Send + Sync> {
+    match self {
+      InputBackendKind::Global => return Box::new(GlobalBackend),
+      InputBackendKind::Targeted => return Box::new(TargetedBackend::new(matcher)),
+      InputBackendKind::DryRun => return Box::new(DryRunBackend)
+    }
+  }
+
+  pub fn is_targeted(&self) -> bool {
+    match self {
+      InputBackendKind::Targeted => return true,
+      _ => return false
+    }
+  }
+
+  // Whether keys like Shift can be held down while other keys are pressed.
+  // Windows only lets the targeted backend post key messages, which don't
+  // change whether the game thinks Shift is held.
+  pub fn can_hold_modifiers(&self) -> bool {
+    return !(cfg!(windows) && self.is_targeted());
+  }
+}
+
+// Sends keystrokes to whatever window has focus, just like a real keyboard.
+pub struct GlobalBackend;
+
+impl InputBackend for GlobalBackend {
+  fn press(&self, key: KeybdKey) -> () {
+    key.press();
+  }
+
+  fn release(&self, key: KeybdKey) -> () {
+    key.release();
+  }
+}
+
+fn is_modifier(key: KeybdKey) -> bool {
+  match key {
+    KeybdKey::LShiftKey | KeybdKey::RShiftKey | KeybdKey::LControlKey | KeybdKey::RControlKey => return true,
+    _ => return false
+  }
+}
+
+// Sends keystrokes straight to the game's window, so it doesn't need focus and
+// the streamer is free to use other windows. The window is looked up on every
+// keystroke, in case the game was restarted.
+pub struct TargetedBackend {
+  matcher: Arc<WindowMatcher>,
+  // Modifiers that have been pressed but not released yet, like the Shift
+  // that FTL depowers with. Keys sent to a window don't hold anything down on
+  // the real keyboard, so every event has to say what's held.
+  held: Mutex<Vec<KeybdKey>>
+}
+
+impl TargetedBackend {
+  pub fn new(matcher: Arc<WindowMatcher>) -> TargetedBackend {
+    return TargetedBackend {
+      matcher: matcher,
+      held: Mutex::new(Vec::new())
+    }
+  }
+
+  fn send(&self, key: KeybdKey, pressed: bool) -> () {
+    let mut held = self.held.lock().expect("Expected the held keys' lock to not be poisoned.");
+    // Like a real keyboard, a modifier's own event doesn't count it as held.
+    if let Some(window) = Window::find(|window| self.matcher.matches(window)) {
+      window.send_key(key, pressed, &held);
+    }
+    if is_modifier(key) {
+      held.retain(|held_key| *held_key != key);
+      if pressed {
+        held.push(key);
+      }
+    }
+  }
+}
+
+impl InputBackend for TargetedBackend {
+  fn press(&self, key: KeybdKey) -> () {
+    self.send(key, true);
+  }
+
+  fn release(&self, key: KeybdKey) -> () {
+    self.send(key, false);
+  }
+}
+
+// Prints keystrokes instead of sending them, for trying out the bot without
+// it mashing keys in whatever window happens to be open.
+pub struct DryRunBackend;
+
+impl InputBackend for DryRunBackend {
+  fn press(&self, key: KeybdKey) -> () {
+    println!("Pressed {:?}", key);
+  }
+
+  fn release(&self, key: KeybdKey) -> () {
+    println!("Released {:?}", key);
+  }
+}
\ No newline at end of file
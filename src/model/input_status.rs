@@ -0,0 +1,23 @@
+// Where an input is in its life, shown as a reaction on the message it came
+// from. Each status replaces the last, so the message only ever shows one.
+#[derive(Copy, Clone)]
+pub enum InputStatus {
+  Queued,
+  Executed,
+  // Couldn't be parsed, or wasn't allowed. The reason is given in a reply.
+  Rejected,
+  // Queued, but thrown away before it finished, like when the game lost focus
+  // or the queue overflowed.
+  Dropped
+}
+
+impl InputStatus {
+  pub fn emoji(&self) -> &'static str {
+    match self {
+      InputStatus::Queued => return "⏳",
+      InputStatus::Executed => return "✅",
+      InputStatus::Rejected => return "❌",
+      InputStatus::Dropped => return "🗑️"
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,659 @@
+use crate::model::{
+  bot::Bot,
+  chat::{Chat, ChatMessage, StatusUpdate, name_id},
+  dispatcher::Dispatcher,
+  error::{ConfigIssue, PlatformError},
+  input_status::InputStatus,
+  secret::Secret
+};
+use futures_util::FutureExt;
+use serde::{Serialize, Deserialize};
+use serenity::async_trait;
+use std::{
+  collections::HashMap,
+  env,
+  fs::File,
+  io::BufReader as StdBufReader,
+  sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}},
+  time::Duration
+};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, WriteHalf},
+  net::TcpStream,
+  sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel}
+};
+use tokio_rustls::{
+  TlsConnector,
+  rustls::ClientConfig,
+  webpki::DNSNameRef
+};
+use tracing::{info, warn};
+
+// Where the bot connects to when `platform` is "Irc", and who it connects as.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IrcConfig {
+  server: String,
+  port: u16,
+  tls: bool,
+  // A PEM file with the certificates to trust instead of the usual ones, for
+  // servers with self-signed certificates.
+  ca_file: Option<String>,
+  nick: String,
+  channels: Vec<String>,
+  // Logs in with SASL if a user is set. Like the token, the password can come
+  // from an environment variable instead, which wins if both are set.
+  sasl_user: Option<String>,
+  sasl_password_env: Option<String>,
+  sasl_password: Option<Secret>,
+  // Nicks that can use admin commands. On servers that say which account
+  // someone's logged into, these are account names instead.
+  admins: Vec<String>
+}
+
+impl Default for IrcConfig {
+  fn default() -> IrcConfig {
+    return IrcConfig {
+      server: String::new(),
+      port: 6697,
+      tls: true,
+      ca_file: None,
+      nick: "crowdplay".to_string(),
+      channels: Vec::new(),
+      sasl_user: None,
+      sasl_password_env: None,
+      sasl_password: None,
+      admins: Vec::new()
+    }
+  }
+}
+
+impl IrcConfig {
+  fn resolve_password(&self) -> Result<Option<Secret>, String> {
+    match (&self.sasl_password_env, &self.sasl_password) {
+      (Some(var), _) => match env::var(var) {
+        Err(_) => return Err(format!("The environment variable \"{}\" isn't set.", var)),
+        Ok(password) => return Ok(Some(Secret::new(password)))
+      },
+      (None, password) => return Ok(password.clone())
+    }
+  }
+
+  // Everything that can be checked without connecting.
+  pub fn check(&self, issues: &mut Vec<ConfigIssue>) -> () {
+    if self.server.is_empty() {
+      issues.push(ConfigIssue::new(
+        "irc.server",
+        "No server is set.",
+        "Set it to the IRC server's address, like \"irc.example.com\"."
+      ));
+    }
+    if self.nick.is_empty() || self.nick.contains(|c: char| c.is_whitespace() || c == ',' || c == ':') {
+      issues.push(ConfigIssue::new(
+        "irc.nick",
+        &format!("\"{}\" isn't a valid nick.", self.nick),
+        "Use a single word, like \"crowdplay\"."
+      ));
+    }
+    if self.channels.is_empty() {
+      issues.push(ConfigIssue::new(
+        "irc.channels",
+        "No channels are set, so no messages would be read.",
+        "Add a channel like \"#crowdplay\"."
+      ));
+    }
+    for channel in &self.channels {
+      if !channel.starts_with('#') && !channel.starts_with('&') {
+        issues.push(ConfigIssue::new(
+          "irc.channels",
+          &format!("\"{}\" isn't a channel name.", channel),
+          "Channel names start with a \"#\", like \"#crowdplay\"."
+        ));
+      }
+    }
+    if self.sasl_user.is_some() {
+      match self.resolve_password() {
+        Err(e) => issues.push(ConfigIssue::new("irc.sasl_password_env", &e, "Set the variable, or remove `sasl_password_env`.")),
+        Ok(None) => issues.push(ConfigIssue::new(
+          "irc.sasl_password",
+          "A SASL user is set, but there's no password to log in with.",
+          "Set `sasl_password` or `sasl_password_env`, or remove `sasl_user`."
+        )),
+        Ok(Some(_)) => ()
+      }
+    }
+    if let Some(path) = &self.ca_file {
+      if File::open(path).is_err() {
+        issues.push(ConfigIssue::new(
+          "irc.ca_file",
+          &format!("\"{}\" couldn't be opened.", path),
+          "Point it at a PEM file, or remove it to trust the usual certificates."
+        ));
+      }
+    }
+  }
+}
+
+// One line from the server, like ":nick!user@host PRIVMSG #channel :hi".
+struct IrcLine<'a> {
+  tags: &'a str,
+  prefix: &'a str,
+  command: &'a str,
+  params: Vec<&'a str>
+}
+
+impl<'a> IrcLine<'a> {
+  fn parse(line: &'a str) -> IrcLine<'a> {
+    let mut rest: &str = line;
+    let mut tags: &str = "";
+    let mut prefix: &str = "";
+    if let Some(tagged) = rest.strip_prefix('@') {
+      let (found, after) = tagged.split_once(' ').unwrap_or((tagged, ""));
+      tags = found;
+      rest = after.trim_start();
+    }
+    if let Some(prefixed) = rest.strip_prefix(':') {
+      let (found, after) = prefixed.split_once(' ').unwrap_or((prefixed, ""));
+      prefix = found;
+      rest = after.trim_start();
+    }
+    let (command, mut rest) = rest.split_once(' ').unwrap_or((rest, ""));
+
+    // Everything after a ':' is one last parameter, spaces and all.
+    let mut params: Vec<&str> = Vec::new();
+    while !rest.is_empty() {
+      if let Some(trailing) = rest.strip_prefix(':') {
+        params.push(trailing);
+        break;
+      }
+      let (param, after) = rest.split_once(' ').unwrap_or((rest, ""));
+      params.push(param);
+      rest = after.trim_start();
+    }
+    return IrcLine {
+      tags: tags,
+      prefix: prefix,
+      command: command,
+      params: params
+    }
+  }
+
+  // The nick of whoever sent the line.
+  fn nick(&self) -> &'a str {
+    return self.prefix.split('!').next().unwrap_or("");
+  }
+
+  fn tag(&self, name: &str) -> Option<&'a str> {
+    return self.tags.split(';')
+      .filter_map(|tag| tag.split_once('='))
+      .find(|(key, _)| *key == name)
+      .map(|(_, value)| value);
+  }
+
+  fn param(&self, i: usize) -> &'a str {
+    return self.params.get(i).copied().unwrap_or("");
+  }
+}
+
+// Logged-in users are known by their account rather than their nick, so that
+// bans and stats follow them from nick to nick. Nicks can't have spaces, so
+// the two can't end up with the same ID.
+fn user_id(nick: &str, account: Option<&str>) -> u64 {
+  match account {
+    None => return name_id(nick),
+    Some(account) => return name_id(&format!("account {}", account))
+  }
+}
+
+// Talks back to IRC by queueing up lines for the connection to send. IRC has no
+// reactions, so the only status anyone's told about is their input being
+// dropped, since replies already explain rejections.
+struct IrcChat {
+  outgoing: UnboundedSender<String>,
+  // The nick of everyone who's said something, and the name of every channel
+  // they've said it in, by the ID made from them.
+  names: Mutex<HashMap<u64, String>>,
+  // The ID of everyone who's said something, by their lowercased nick, since
+  // logged-in users' IDs can't be made from their nick alone.
+  ids: Mutex<HashMap<String, u64>>,
+  // The message being handled right now, and every queued input's message,
+  // so that whoever sent one can be told if it's dropped.
+  handling: Mutex<Option<(u64, String, String)>>,
+  queued: Mutex<HashMap<u64, (String, String)>>
+}
+
+impl IrcChat {
+  fn send(&self, line: String) -> () {
+    // This only fails once the bot's shutting down.
+    let _ = self.outgoing.send(line);
+  }
+
+  // IRC messages can't span lines, so each line is sent on its own.
+  fn privmsg(&self, target: &str, content: &str) -> () {
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+      self.send(format!("PRIVMSG {} :{}", target, line));
+    }
+  }
+
+  fn name(&self, id: u64) -> Option<String> {
+    return self.names.lock().expect("Expected the names' lock to not be poisoned.").get(&id).cloned();
+  }
+
+  fn remember(&self, msg: &ChatMessage, channel: &str) -> () {
+    let mut names = self.names.lock().expect("Expected the names' lock to not be poisoned.");
+    names.insert(*msg.get_user_id(), msg.get_user().to_string());
+    names.insert(*msg.get_channel_id(), channel.to_string());
+    self.ids.lock()
+      .expect("Expected the IDs' lock to not be poisoned.")
+      .insert(msg.get_user().to_lowercase(), *msg.get_user_id());
+    *self.handling.lock().expect("Expected the handling lock to not be poisoned.") =
+      Some((*msg.get_id(), msg.get_user().to_string(), msg.get_content().to_string()));
+  }
+}
+
+#[async_trait]
+impl Chat for IrcChat {
+  async fn say(&self, channel_id: u64, content: &str) -> () {
+    if let Some(channel) = self.name(channel_id) {
+      self.privmsg(&channel, content);
+    }
+  }
+
+  async fn reply(&self, msg: &ChatMessage, content: &str) -> () {
+    if let Some(channel) = self.name(*msg.get_channel_id()) {
+      self.privmsg(&channel, &format!("{}: {}", msg.get_user(), content));
+    }
+  }
+
+  async fn react(&self, _msg: &ChatMessage, _emoji: &str) -> () {}
+
+  fn update_status(&self, update: StatusUpdate) -> () {
+    let mut queued = self.queued.lock().expect("Expected the queued lock to not be poisoned.");
+    match update.status {
+      InputStatus::Queued => {
+        let handling = self.handling.lock().expect("Expected the handling lock to not be poisoned.");
+        if let Some((id, nick, content)) = &*handling {
+          if *id == update.message_id {
+            queued.insert(*id, (nick.to_string(), content.to_string()));
+          }
+        }
+      },
+      InputStatus::Dropped => {
+        if let Some((nick, content)) = queued.remove(&update.message_id) {
+          self.send(format!("NOTICE {} :Your input \"{}\" was dropped.", nick, content));
+        }
+      },
+      InputStatus::Executed | InputStatus::Rejected => {
+        queued.remove(&update.message_id);
+      }
+    }
+  }
+
+  // IRC doesn't have anything like an activity.
+  async fn set_activity(&self, _activity: &str) -> () {}
+
+  async fn bot_user_id(&self) -> Option<u64> {
+    return None;
+  }
+
+  fn mention(&self, user_id: u64) -> String {
+    return self.name(user_id).unwrap_or("someone".to_string());
+  }
+
+  // Users are mentioned by nick, however chat clients happen to complete it.
+  // Anyone who hasn't said anything yet is taken to not be logged in.
+  fn parse_mention(&self, s: &str) -> Option<u64> {
+    let nick: &str = s.trim_start_matches('@').trim_end_matches(|c| c == ':' || c == ',');
+    if nick.is_empty() {
+      return None;
+    }
+    let ids = self.ids.lock().expect("Expected the IDs' lock to not be poisoned.");
+    return Some(ids.get(&nick.to_lowercase()).copied().unwrap_or(user_id(nick, None)));
+  }
+}
+
+trait IrcStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> IrcStream for T {}
+
+async fn write_line(writer: &mut WriteHalf<Box<dyn IrcStream>>, line: &str) -> Result<(), PlatformError> {
+  match writer.write_all(format!("{}\r\n", line).as_bytes()).await {
+    Err(e) => return Err(PlatformError::Connection(e.to_string())),
+    Ok(_) => return Ok(())
+  }
+}
+
+// How long to wait before reconnecting after the connection drops.
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+// Lines are sent a little apart, since servers kick anyone who floods them.
+const SEND_DELAY: Duration = Duration::from_millis(200);
+
+// Connects to an IRC server and reads commands from its channels. Direct
+// messages are ignored, since only the channels are meant for playing.
+pub struct BotIrc {
+  dispatcher: Arc<Dispatcher>,
+  config: IrcConfig,
+  chat: Arc<IrcChat>,
+  // IRC messages don't have IDs, so they're numbered as they come in. This
+  // carries on across reconnects, since inputs from before one can still be
+  // queued.
+  next_id: AtomicU64,
+  // Taken out once the bot starts.
+  outgoing: Option<UnboundedReceiver<String>>
+}
+
+impl BotIrc {
+  pub fn new(dispatcher: Dispatcher, config: IrcConfig) -> BotIrc {
+    let (sender, receiver) = unbounded_channel::<String>();
+    return BotIrc {
+      dispatcher: Arc::new(dispatcher),
+      config: config,
+      chat: Arc::new(IrcChat {
+        outgoing: sender,
+        names: Mutex::new(HashMap::new()),
+        ids: Mutex::new(HashMap::new()),
+        handling: Mutex::new(None),
+        queued: Mutex::new(HashMap::new())
+      }),
+      next_id: AtomicU64::new(1),
+      outgoing: Some(receiver)
+    }
+  }
+
+  async fn connect(&self) -> Result<Box<dyn IrcStream>, PlatformError> {
+    let address: String = format!("{}:{}", self.config.server, self.config.port);
+    let tcp: TcpStream = match TcpStream::connect(&address).await {
+      Err(e) => return Err(PlatformError::Connection(format!("Couldn't reach {}: {}", address, e))),
+      Ok(tcp) => tcp
+    };
+    if !self.config.tls {
+      return Ok(Box::new(tcp));
+    }
+
+    let mut tls_config: ClientConfig = ClientConfig::new();
+    match &self.config.ca_file {
+      None => tls_config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS),
+      Some(path) => {
+        let file: File = File::open(path)
+          .map_err(|e| PlatformError::Client(format!("Couldn't open \"{}\": {}", path, e)))?;
+        if tls_config.root_store.add_pem_file(&mut StdBufReader::new(file)).is_err() {
+          return Err(PlatformError::Client(format!("\"{}\" isn't a PEM file.", path)));
+        }
+      }
+    }
+    let domain: DNSNameRef = DNSNameRef::try_from_ascii_str(&self.config.server)
+      .map_err(|_| PlatformError::Client(format!(
+        "\"{}\" isn't a host name, which TLS needs to check the certificate against.",
+        self.config.server
+      )))?;
+    match TlsConnector::from(Arc::new(tls_config)).connect(domain, tcp).await {
+      Err(e) => return Err(PlatformError::Connection(format!("Couldn't set up TLS: {}", e))),
+      Ok(tls) => return Ok(Box::new(tls))
+    }
+  }
+
+  fn is_admin(&self, line: &IrcLine, account_tags: bool) -> bool {
+    // Once the server tags messages with accounts, nicks can't be trusted,
+    // since anyone can take an admin's nick while they're away.
+    let name: Option<&str> = match account_tags {
+      true => line.tag("account"),
+      false => Some(line.nick())
+    };
+    return name.map_or(false, |name| self.config.admins.iter().any(|admin| admin.eq_ignore_ascii_case(name)));
+  }
+
+  fn sender_id(line: &IrcLine, account_tags: bool) -> u64 {
+    let account: Option<&str> = match account_tags {
+      true => line.tag("account"),
+      false => None
+    };
+    return user_id(line.nick(), account);
+  }
+
+  async fn handle_privmsg(&self, line: &IrcLine<'_>, account_tags: bool, id: u64) -> () {
+    let channel: &str = line.param(0);
+    let content: &str = line.param(1);
+    // Anything wrapped in \x01 is a CTCP request, like "/me", rather than chat.
+    if !channel.starts_with('#') && !channel.starts_with('&') || content.starts_with('\x01') {
+      return;
+    }
+
+    let msg: ChatMessage = ChatMessage::new(
+      id,
+      name_id(channel),
+      BotIrc::sender_id(line, account_tags),
+      line.nick().to_string(),
+      self.is_admin(line, account_tags),
+      content.to_string()
+    );
+    self.chat.remember(&msg, channel);
+    self.dispatcher.handle_message(&*self.chat, msg).await;
+  }
+
+  // Runs one connection until it drops, which it always eventually does.
+  // `registered` is set once the server's let the bot in, so that problems
+  // from then on can be told apart from ones like a wrong password.
+  async fn session(&self, outgoing: &mut UnboundedReceiver<String>, registered: &mut bool) -> PlatformError {
+    let stream: Box<dyn IrcStream> = match self.connect().await {
+      Err(e) => return e,
+      Ok(stream) => stream
+    };
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    // Anything said while disconnected is old news by now.
+    while let Some(Some(_)) = outgoing.recv().now_or_never() {}
+
+    let password: Option<Secret> = match self.config.resolve_password() {
+      Err(e) => return PlatformError::Client(e),
+      Ok(password) => password
+    };
+    let sasl: Option<(&String, Secret)> = self.config.sasl_user.as_ref().zip(password);
+    let mut nick: String = self.config.nick.to_string();
+    // Capabilities are asked for one at a time, so that a server turning one
+    // down doesn't turn down the others along with it.
+    let mut pending_caps: usize = 1;
+    let mut account_tags: bool = false;
+    let mut authenticating: bool = false;
+
+    let mut greeting: Vec<String> = vec!["CAP REQ :account-tag".to_string()];
+    if sasl.is_some() {
+      greeting.push("CAP REQ :sasl".to_string());
+      pending_caps += 1;
+    }
+    greeting.push(format!("NICK {}", nick));
+    greeting.push(format!("USER {} 0 * :crowdplay-bot-rs", nick));
+    for line in greeting {
+      if let Err(e) = write_line(&mut writer, &line).await {
+        return e;
+      }
+    }
+
+    loop {
+      let mut replies: Vec<String> = Vec::new();
+      tokio::select! {
+        read = lines.next_line() => {
+          let raw: String = match read {
+            Err(e) => return PlatformError::Connection(e.to_string()),
+            Ok(None) => return PlatformError::Connection("The server closed the connection.".to_string()),
+            Ok(Some(raw)) => raw
+          };
+          let line: IrcLine = IrcLine::parse(&raw);
+          match line.command {
+            "PING" => replies.push(format!("PONG :{}", line.param(0))),
+            "CAP" if line.param(1) == "ACK" || line.param(1) == "NAK" => {
+              let acked: bool = line.param(1) == "ACK";
+              pending_caps = pending_caps.saturating_sub(1);
+              match line.param(2).trim() {
+                "account-tag" => account_tags = acked,
+                "sasl" if acked => {
+                  authenticating = true;
+                  replies.push("AUTHENTICATE PLAIN".to_string());
+                },
+                "sasl" => return PlatformError::Connection("The server doesn't support SASL.".to_string()),
+                _ => ()
+              }
+              if pending_caps == 0 && !authenticating {
+                replies.push("CAP END".to_string());
+              }
+            },
+            "AUTHENTICATE" if line.param(0) == "+" => {
+              if let Some((user, password)) = &sasl {
+                let credentials: String = format!("{}\0{}\0{}", user, user, password.expose());
+                replies.push(format!("AUTHENTICATE {}", base64::encode(credentials)));
+              }
+            },
+            // Logged in.
+            "903" => {
+              authenticating = false;
+              if pending_caps == 0 {
+                replies.push("CAP END".to_string());
+              }
+            },
+            "902" | "904" | "905" | "906" | "908" => {
+              return PlatformError::Connection("SASL authentication failed.".to_string());
+            },
+            // The nick's taken, so try another one.
+            "433" if !*registered => {
+              nick.push('_');
+              replies.push(format!("NICK {}", nick));
+            },
+            // Welcome to the server.
+            "001" => {
+              *registered = true;
+              nick = line.param(0).to_string();
+              info!(server = %self.config.server, nick = %nick, "Connected to IRC");
+              replies.push(format!("JOIN {}", self.config.channels.join(",")));
+              self.dispatcher.start(Arc::clone(&self.chat) as Arc<dyn Chat + Send + Sync>);
+            },
+            "PRIVMSG" if *registered => {
+              let id: u64 = self.next_id.fetch_add(1, Ordering::Relaxed);
+              self.handle_privmsg(&line, account_tags, id).await;
+            },
+            "ERROR" => return PlatformError::Connection(line.param(0).to_string()),
+            _ => ()
+          }
+        },
+        Some(out) = outgoing.recv(), if *registered => {
+          if let Err(e) = write_line(&mut writer, &out).await {
+            return e;
+          }
+          tokio::time::sleep(SEND_DELAY).await;
+        }
+      }
+      for reply in replies {
+        if let Err(e) = write_line(&mut writer, &reply).await {
+          return e;
+        }
+      }
+    }
+  }
+}
+
+#[async_trait]
+impl Bot for BotIrc {
+  async fn start(&mut self) -> Result<(), PlatformError> {
+    let mut outgoing: UnboundedReceiver<String> = match self.outgoing.take() {
+      None => return Err(PlatformError::Client("The bot's already been started.".to_string())),
+      Some(outgoing) => outgoing
+    };
+    self.dispatcher.serve();
+
+    // Once the bot's made it in, it keeps trying to get back in, the same way
+    // it would on Discord.
+    let mut connected: bool = false;
+    loop {
+      let mut registered: bool = false;
+      let e: PlatformError = self.session(&mut outgoing, &mut registered).await;
+      connected = connected || registered;
+      if !connected {
+        return Err(e);
+      }
+      warn!(error = %e, "Lost the connection to IRC, so reconnecting in a bit");
+      tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+  }
+
+  async fn flush(&self) -> () {
+    self.dispatcher.flush().await;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn chat() -> IrcChat {
+    let (sender, _) = unbounded_channel::<String>();
+    return IrcChat {
+      outgoing: sender,
+      names: Mutex::new(HashMap::new()),
+      ids: Mutex::new(HashMap::new()),
+      handling: Mutex::new(None),
+      queued: Mutex::new(HashMap::new())
+    }
+  }
+
+  #[test]
+  fn logged_in_users_are_known_by_their_account() {
+    assert_eq!(user_id("alice", None), name_id("alice"));
+    assert_eq!(user_id("alice_", Some("alice")), user_id("Alice", Some("alice")));
+    assert_ne!(user_id("alice", Some("alice")), user_id("alice", None));
+  }
+
+  #[test]
+  fn mentions_use_the_id_nicks_last_spoke_with() {
+    let chat: IrcChat = chat();
+    let id: u64 = user_id("alice_", Some("alice"));
+    chat.remember(&ChatMessage::new(1, name_id("#play"), id, "alice_".to_string(), false, "!a".to_string()), "#play");
+    assert_eq!(chat.parse_mention("Alice_:"), Some(id));
+    assert_eq!(chat.parse_mention("@bob"), Some(user_id("bob", None)));
+    assert_eq!(chat.parse_mention("@"), None);
+  }
+
+  #[test]
+  fn parses_tags_prefix_and_trailing_param() {
+    let line: IrcLine = IrcLine::parse("@account=alice;time=now :alice!a@host PRIVMSG #play :!up 3");
+    assert_eq!(line.tag("account"), Some("alice"));
+    assert_eq!(line.tag("time"), Some("now"));
+    assert_eq!(line.tag("missing"), None);
+    assert_eq!(line.prefix, "alice!a@host");
+    assert_eq!(line.nick(), "alice");
+    assert_eq!(line.command, "PRIVMSG");
+    assert_eq!(line.params, vec!["#play", "!up 3"]);
+  }
+
+  #[test]
+  fn parses_lines_without_tags_or_prefix() {
+    let line: IrcLine = IrcLine::parse("PING :irc.example.com");
+    assert_eq!(line.tags, "");
+    assert_eq!(line.prefix, "");
+    assert_eq!(line.command, "PING");
+    assert_eq!(line.param(0), "irc.example.com");
+  }
+
+  #[test]
+  fn parses_middle_params() {
+    let line: IrcLine = IrcLine::parse(":srv CAP * ACK :account-tag sasl");
+    assert_eq!(line.command, "CAP");
+    assert_eq!(line.params, vec!["*", "ACK", "account-tag sasl"]);
+  }
+
+  #[test]
+  fn parses_lines_without_params() {
+    let line: IrcLine = IrcLine::parse(":srv AUTHENTICATE");
+    assert_eq!(line.command, "AUTHENTICATE");
+    assert!(line.params.is_empty());
+    assert_eq!(line.param(0), "");
+
+    let line: IrcLine = IrcLine::parse("QUIT");
+    assert_eq!(line.command, "QUIT");
+    assert!(line.params.is_empty());
+  }
+
+  #[test]
+  fn keeps_empty_trailing_params() {
+    let line: IrcLine = IrcLine::parse(":alice!a@host PRIVMSG #play :");
+    assert_eq!(line.params, vec!["#play", ""]);
+  }
+}
\ No newline at end of file
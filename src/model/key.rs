@@ -1,3 +1,7 @@
+use crate::model::{
+  error::InputError,
+  input_backend::InputBackend
+};
 use inputbot::KeybdKey;
 use std::{
   convert::TryFrom,
@@ -11,9 +15,12 @@ pub trait KeyInputtable {
   // of mutability in Rust is still pretty rough.
   // Executes the input once, then returns the next input. The next input is
   // effectively the same input, but with one less button press.
-  fn pop(&self) -> Option<Box<dyn KeyInputtable + Send + Sync>>;
+  fn pop(&self, backend: &dyn InputBackend) -> Result<Option<Box<dyn KeyInputtable + Send + Sync>>, InputError>;
 
   fn get_presses(&self) -> &i8;
+
+  // The keys that get pressed together on each press, in order.
+  fn get_keys(&self) -> Vec<KeybdKey>;
 }
 
 pub trait KeyMappable {
@@ -38,29 +45,33 @@ impl KeyInput {
 
 impl KeyInputtable for KeyInput {
 
-  fn pop(&self) -> Option<Box<dyn KeyInputtable + Send + Sync>> {
+  fn pop(&self, backend: &dyn InputBackend) -> Result<Option<Box<dyn KeyInputtable + Send + Sync>>, InputError> {
     match self.presses {
-      0 => return None,
+      // Negative presses would otherwise never count down to zero.
+      i if i <= 0 => return Ok(None),
       _ => {
-        let converted_delay: u64 = u64::try_from(self.delay).unwrap();
+        let converted_delay: u64 = match u64::try_from(self.delay) {
+          Err(_) => return Err(InputError::InvalidDelay(self.delay)),
+          Ok(delay) => delay
+        };
         for i in 0..self.keys.len() {
-          self.keys[i].press();
+          backend.press(self.keys[i]);
           sleep(Duration::from_millis(converted_delay));
         }
         // 75ms is good for Pokemon
         // sleep(Duration::from_millis(75));
         for i in 0..self.keys.len() {
-          self.keys[i].release();
+          backend.release(self.keys[i]);
           // sleep(Duration::from_millis(converted_delay));
         }
         // sleep(Duration::from_millis(75));
-        return Some(Box::new(
+        return Ok(Some(Box::new(
           KeyInput::new(
             self.keys.clone(), 
             self.presses - 1, 
             self.delay
           )
-        ));
+        )));
       }
     }
   }
@@ -68,4 +79,8 @@ impl KeyInputtable for KeyInput {
   fn get_presses(&self) -> &i8 {
     return &self.presses;
   }
+
+  fn get_keys(&self) -> Vec<KeybdKey> {
+    return self.keys.clone();
+  }
 }
\ No newline at end of file
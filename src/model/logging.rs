@@ -0,0 +1,50 @@
+use serde::{Serialize, Deserialize};
+use std::{fs::File, io, sync::Mutex};
+use tracing::{Subscriber, level_filters::LevelFilter};
+use tracing_subscriber::{filter::Targets, fmt, layer::SubscriberExt};
+
+// Anything logged from outside of this crate, like serenity's own logging, is
+// filtered separately so that it doesn't drown out the bot's.
+const CRATE_TARGET: &str = "crowdplay_bot_rs";
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum LogLevel {
+  Off,
+  Error,
+  Warn,
+  Info,
+  Debug,
+  Trace
+}
+
+impl LogLevel {
+  pub fn to_filter(&self) -> LevelFilter {
+    match self {
+      LogLevel::Off => return LevelFilter::OFF,
+      LogLevel::Error => return LevelFilter::ERROR,
+      LogLevel::Warn => return LevelFilter::WARN,
+      LogLevel::Info => return LevelFilter::INFO,
+      LogLevel::Debug => return LevelFilter::DEBUG,
+      LogLevel::Trace => return LevelFilter::TRACE
+    }
+  }
+}
+
+pub type Logger = Box<dyn Subscriber + Send + Sync>;
+
+// Prints everything to the console, and optionally writes it to a file as well,
+// one JSON object per line so that it's easy to search through.
+pub fn build_logger(level: LogLevel, library_level: LogLevel, file: Option<File>) -> Logger {
+  let filter: Targets = Targets::new()
+    .with_default(library_level.to_filter())
+    .with_target(CRATE_TARGET, level.to_filter());
+  let file_layer = file.map(|file| fmt::layer()
+    .json()
+    .with_ansi(false)
+    .with_writer(Mutex::new(file)));
+
+  return Box::new(tracing_subscriber::registry()
+    .with(filter)
+    .with(fmt::layer().with_writer(io::stderr))
+    .with(file_layer));
+}
\ No newline at end of file
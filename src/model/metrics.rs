@@ -0,0 +1,217 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::{error::ParseError, http::not_found, queue::SharedInputStack};
+use hyper::{
+  Body,
+  Method,
+  Request,
+  Response,
+  server::{Builder, conn::AddrIncoming},
+  service::{make_service_fn, service_fn}
+};
+use std::{
+  collections::HashMap,
+  convert::Infallible,
+  fmt::Write,
+  sync::{
+    Arc,
+    Mutex,
+    atomic::{
+      AtomicBool,
+      AtomicU64,
+      Ordering
+    }
+  },
+  time::Duration
+};
+use tracing::{error, info};
+
+// In seconds. Most inputs should land in the first few buckets, but a long
+// queue can easily keep someone waiting for minutes.
+const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+struct Histogram {
+  // How many observations were at or under each bucket, in the same order as
+  // LATENCY_BUCKETS.
+  buckets: Vec<u64>,
+  sum: f64,
+  count: u64
+}
+
+// Counters for everything that happens to a command, in a form that
+// Prometheus can scrape.
+pub struct Metrics {
+  commands_received: AtomicU64,
+  inputs_queued: AtomicU64,
+  // Keyed by why commands were rejected, why inputs were dropped, and which
+  // game and command were executed.
+  commands_rejected: Mutex<HashMap<&'static str, u64>>,
+  inputs_dropped: Mutex<HashMap<&'static str, u64>>,
+  inputs_executed: Mutex<HashMap<(&'static str, String), u64>>,
+  // How long inputs take from being queued to being done.
+  input_latency: Mutex<Histogram>
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+  return mutex.lock().expect("Expected a metric's lock to not be poisoned.");
+}
+
+fn rejection_reason(e: &ParseError) -> &'static str {
+  match e {
+    ParseError::Empty => return "empty",
+    ParseError::UnknownCommand(_) => return "unknown_command",
+    ParseError::UnknownGame(_) => return "unknown_game",
+    ParseError::InvalidArgument(_) => return "invalid_argument",
+    ParseError::RepeatLimit(_, _, _) | ParseError::RepeatCount(_) => return "repeat_limit"
+  }
+}
+
+// Label values can contain anything but these.
+fn escape_label(value: &str) -> String {
+  return value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+}
+
+impl Metrics {
+  pub fn new() -> Metrics {
+    return Metrics {
+      commands_received: AtomicU64::new(0),
+      inputs_queued: AtomicU64::new(0),
+      commands_rejected: Mutex::new(HashMap::new()),
+      inputs_dropped: Mutex::new(HashMap::new()),
+      inputs_executed: Mutex::new(HashMap::new()),
+      input_latency: Mutex::new(Histogram {
+        buckets: vec![0; LATENCY_BUCKETS.len()],
+        sum: 0.0,
+        count: 0
+      })
+    }
+  }
+
+  pub fn command_received(&self) -> () {
+    self.commands_received.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub fn input_queued(&self) -> () {
+    self.inputs_queued.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub fn command_rejected(&self, e: &ParseError) -> () {
+    *lock(&self.commands_rejected).entry(rejection_reason(e)).or_insert(0) += 1;
+  }
+
+  pub fn inputs_dropped(&self, reason: &'static str, count: usize) -> () {
+    *lock(&self.inputs_dropped).entry(reason).or_insert(0) += count as u64;
+  }
+
+  // Only the command's name is kept, since repeat counts would give every
+  // number its own label.
+  pub fn input_executed(&self, game: &SupportedGame, command: &str, latency: Duration) -> () {
+    let name: String = command.split_whitespace().next().unwrap_or("").to_string();
+    *lock(&self.inputs_executed).entry((game.to_id(), name)).or_insert(0) += 1;
+
+    let seconds: f64 = latency.as_secs_f64();
+    let mut histogram = lock(&self.input_latency);
+    for (i, bucket) in LATENCY_BUCKETS.iter().enumerate() {
+      if seconds <= *bucket {
+        histogram.buckets[i] += 1;
+      }
+    }
+    histogram.sum += seconds;
+    histogram.count += 1;
+  }
+
+  // Everything in Prometheus' text format. The queue is read as it is right
+  // now, rather than being tracked.
+  pub fn render(&self, queue_depth: usize, paused: bool) -> String {
+    let mut out: String = String::new();
+
+    let _ = writeln!(out, "# HELP crowdplay_queue_depth Inputs waiting to be executed.");
+    let _ = writeln!(out, "# TYPE crowdplay_queue_depth gauge");
+    let _ = writeln!(out, "crowdplay_queue_depth {}", queue_depth);
+
+    let _ = writeln!(out, "# HELP crowdplay_paused Whether inputs are paused by an admin.");
+    let _ = writeln!(out, "# TYPE crowdplay_paused gauge");
+    let _ = writeln!(out, "crowdplay_paused {}", paused as u8);
+
+    let _ = writeln!(out, "# HELP crowdplay_commands_received_total Commands read from chat, including rejected ones.");
+    let _ = writeln!(out, "# TYPE crowdplay_commands_received_total counter");
+    let _ = writeln!(out, "crowdplay_commands_received_total {}", self.commands_received.load(Ordering::Relaxed));
+
+    let _ = writeln!(out, "# HELP crowdplay_inputs_queued_total Commands that were parsed and queued.");
+    let _ = writeln!(out, "# TYPE crowdplay_inputs_queued_total counter");
+    let _ = writeln!(out, "crowdplay_inputs_queued_total {}", self.inputs_queued.load(Ordering::Relaxed));
+
+    let _ = writeln!(out, "# HELP crowdplay_commands_rejected_total Commands that couldn't be parsed, by reason.");
+    let _ = writeln!(out, "# TYPE crowdplay_commands_rejected_total counter");
+    for (reason, count) in lock(&self.commands_rejected).iter() {
+      let _ = writeln!(out, "crowdplay_commands_rejected_total{{reason=\"{}\"}} {}", reason, count);
+    }
+
+    let _ = writeln!(out, "# HELP crowdplay_inputs_dropped_total Queued inputs that were never finished, by reason.");
+    let _ = writeln!(out, "# TYPE crowdplay_inputs_dropped_total counter");
+    for (reason, count) in lock(&self.inputs_dropped).iter() {
+      let _ = writeln!(out, "crowdplay_inputs_dropped_total{{reason=\"{}\"}} {}", reason, count);
+    }
+
+    let _ = writeln!(out, "# HELP crowdplay_inputs_executed_total Inputs that were executed, by game and command.");
+    let _ = writeln!(out, "# TYPE crowdplay_inputs_executed_total counter");
+    for ((game, command), count) in lock(&self.inputs_executed).iter() {
+      let _ = writeln!(
+        out,
+        "crowdplay_inputs_executed_total{{game=\"{}\",command=\"{}\"}} {}",
+        game, escape_label(command), count
+      );
+    }
+
+    let histogram = lock(&self.input_latency);
+    let _ = writeln!(out, "# HELP crowdplay_input_latency_seconds Time from an input being queued to it being executed.");
+    let _ = writeln!(out, "# TYPE crowdplay_input_latency_seconds histogram");
+    for (i, bucket) in LATENCY_BUCKETS.iter().enumerate() {
+      let _ = writeln!(out, "crowdplay_input_latency_seconds_bucket{{le=\"{}\"}} {}", bucket, histogram.buckets[i]);
+    }
+    let _ = writeln!(out, "crowdplay_input_latency_seconds_bucket{{le=\"+Inf\"}} {}", histogram.count);
+    let _ = writeln!(out, "crowdplay_input_latency_seconds_sum {}", histogram.sum);
+    let _ = writeln!(out, "crowdplay_input_latency_seconds_count {}", histogram.count);
+
+    return out;
+  }
+}
+
+async fn respond(req: Request<Body>, metrics: &Metrics, input_stack: &SharedInputStack, paused: &AtomicBool)
+  -> Response<Body> {
+  match (req.method(), req.uri().path()) {
+    (&Method::GET, "/metrics") => {
+      let queue_depth: usize = input_stack.read().await.len();
+      return Response::builder()
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .body(Body::from(metrics.render(queue_depth, paused.load(Ordering::Relaxed))))
+        .expect("Expected the metrics response to be valid.");
+    },
+    _ => return not_found()
+  }
+}
+
+// Serves /metrics until the bot stops.
+pub async fn serve_metrics(server: Builder<AddrIncoming>, metrics: Arc<Metrics>,
+  input_stack: SharedInputStack, paused: Arc<AtomicBool>) -> () {
+  let make_service = make_service_fn(move |_conn| {
+    let metrics: Arc<Metrics> = Arc::clone(&metrics);
+    let input_stack: SharedInputStack = Arc::clone(&input_stack);
+    let paused: Arc<AtomicBool> = Arc::clone(&paused);
+    async move {
+      return Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+        let metrics: Arc<Metrics> = Arc::clone(&metrics);
+        let input_stack: SharedInputStack = Arc::clone(&input_stack);
+        let paused: Arc<AtomicBool> = Arc::clone(&paused);
+        async move {
+          return Ok::<_, Infallible>(respond(req, &metrics, &input_stack, &paused).await);
+        }
+      }));
+    }
+  });
+
+  let server = server.serve(make_service);
+  info!(address = %server.local_addr(), "Serving metrics");
+  if let Err(e) = server.await {
+    error!(error = %e, "The metrics server stopped");
+  }
+}
\ No newline at end of file
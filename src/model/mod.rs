@@ -1,4 +1,35 @@
+pub mod admin;
 pub mod app_model;
+pub mod audit;
+pub mod ban_list;
 pub mod bot;
+pub mod chat;
+pub mod command_text;
+pub mod config_watcher;
+pub mod console;
+pub mod dispatcher;
+pub mod error;
+pub mod execution_time;
+pub mod focus_loss;
+pub mod focus_rule;
+pub mod http;
+pub mod input_backend;
+pub mod input_status;
+pub mod irc;
 pub mod key;
-pub mod win_utils;
\ No newline at end of file
+pub mod logging;
+pub mod metrics;
+pub mod overlay;
+pub mod prefix;
+pub mod queue;
+pub mod repeat_policy;
+pub mod scope;
+pub mod secret;
+pub mod settings;
+pub mod user_stats;
+pub mod viewer;
+#[cfg(windows)]
+pub mod win_utils;
+pub mod window;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod x11_utils;
\ No newline at end of file
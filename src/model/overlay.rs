@@ -0,0 +1,242 @@
+use crate::model::{
+  ban_list::unix_now,
+  http::not_found,
+  queue::{QueuedInput, SharedInputStack},
+  settings::SharedSettings
+};
+use async_tungstenite::{
+  WebSocketStream,
+  tokio::TokioAdapter,
+  tungstenite::{
+    Message,
+    handshake::server::create_response,
+    protocol::Role
+  }
+};
+use futures_util::{SinkExt, StreamExt};
+use hyper::{
+  Body,
+  Method,
+  Request,
+  Response,
+  server::{Builder, conn::AddrIncoming},
+  service::{make_service_fn, service_fn},
+  upgrade::Upgraded
+};
+use std::{
+  collections::VecDeque,
+  convert::Infallible,
+  sync::{
+    Arc,
+    Mutex,
+    atomic::{
+      AtomicBool,
+      Ordering
+    }
+  },
+  time::Duration
+};
+use serde_json::{Value, json};
+use tracing::{debug, error, info};
+
+// Meant to be added to OBS as a browser source. It connects back to /ws on its
+// own, so it works from whatever address the overlay is served on.
+const OVERLAY_PAGE: &str = include_str!("overlay.html");
+
+// However many queued inputs are worth showing. Anything past this is only
+// counted, since nobody can read a list that long on stream anyways.
+const MAX_SHOWN_QUEUE: usize = 20;
+
+// How often connected pages are checked for anything new to show.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+struct ExecutedInput {
+  user: String,
+  command: String,
+  // Seconds since the Unix epoch, as of when the input finished.
+  time: u64
+}
+
+// The inputs that were executed most recently, which is the only thing the
+// overlay can't read straight off of the input stack.
+pub struct Overlay {
+  history_len: usize,
+  history: Mutex<VecDeque<ExecutedInput>>
+}
+
+fn queued_to_json(queued: &QueuedInput) -> Value {
+  return json!({
+    "user": queued.get_user(),
+    "command": queued.get_command(),
+    "presses": *queued.get_presses(),
+    "presses_left": queued.get_presses_left()
+  });
+}
+
+impl Overlay {
+  pub fn new(history_len: usize) -> Overlay {
+    return Overlay {
+      history_len: history_len,
+      history: Mutex::new(VecDeque::new())
+    }
+  }
+
+  pub fn executed(&self, queued: &QueuedInput) -> () {
+    let mut history = self.history.lock().expect("Expected the overlay's lock to not be poisoned.");
+    history.push_front(ExecutedInput {
+      user: queued.get_user().to_string(),
+      command: queued.get_command().to_string(),
+      time: unix_now()
+    });
+    history.truncate(self.history_len);
+  }
+
+  // Everything the page shows, as of right now. The queue is in the order it'll
+  // be executed in, which is the reverse of the stack's.
+  async fn snapshot(&self, input_stack: &SharedInputStack, settings: &SharedSettings,
+    paused: &AtomicBool, focused: &AtomicBool) -> Value {
+    let mut state: Value = json!({
+      "game": settings.read().await.get_game().to_string(),
+      "paused": paused.load(Ordering::Relaxed),
+      "focused": focused.load(Ordering::Relaxed)
+    });
+
+    {
+      let input_stack = input_stack.read().await;
+      let mut upcoming = input_stack.iter().rev().peekable();
+      // The next input only counts as being executed once it's been pressed
+      // at least once. Inputs with a single press are done before anyone
+      // could see them here, so those only ever show up in the history.
+      state["current"] = match upcoming.peek() {
+        Some(queued) if queued.get_started_at().is_some() => {
+          let current: Value = queued_to_json(queued);
+          upcoming.next();
+          current
+        },
+        _ => Value::Null
+      };
+      state["queued"] = json!(upcoming.len());
+      state["queue"] = Value::from(upcoming.take(MAX_SHOWN_QUEUE).map(queued_to_json).collect::<Vec<Value>>());
+    }
+
+    let history = self.history.lock().expect("Expected the overlay's lock to not be poisoned.");
+    state["recent"] = Value::from(history.iter().map(|executed| {
+      return json!({
+        "user": executed.user,
+        "command": executed.command,
+        "time": executed.time
+      });
+    }).collect::<Vec<Value>>());
+    return state;
+  }
+}
+
+// Everything the overlay reads from, all of which is shared with the bot.
+#[derive(Clone)]
+pub struct OverlaySource {
+  pub overlay: Arc<Overlay>,
+  pub input_stack: SharedInputStack,
+  pub settings: SharedSettings,
+  pub paused: Arc<AtomicBool>,
+  pub focused: Arc<AtomicBool>
+}
+
+// Sends the page whatever's changed since it was last sent anything, until it
+// disconnects. Pages get the whole state every time, so one that connects
+// partway through doesn't have to piece anything together.
+async fn push_updates(upgraded: Upgraded, source: OverlaySource) -> () {
+  let mut socket: WebSocketStream<TokioAdapter<Upgraded>> =
+    WebSocketStream::from_raw_socket(TokioAdapter(upgraded), Role::Server, None).await;
+  let mut last_sent: String = String::new();
+
+  loop {
+    tokio::select! {
+      incoming = socket.next() => match incoming {
+        None | Some(Err(_)) | Some(Ok(Message::Close(_))) => break,
+        // Pages don't have anything to say. Pings are answered by tungstenite
+        // itself.
+        Some(Ok(_)) => ()
+      },
+      _ = tokio::time::sleep(UPDATE_INTERVAL) => {
+        let state: String = source.overlay.snapshot(
+          &source.input_stack,
+          &source.settings,
+          &source.paused,
+          &source.focused
+        ).await.to_string();
+        if state != last_sent {
+          if socket.send(Message::Text(state.clone())).await.is_err() {
+            break;
+          }
+          last_sent = state;
+        }
+      }
+    }
+  }
+  debug!("An overlay page disconnected");
+}
+
+// Accepts the WebSocket handshake, then hands the connection off once hyper's
+// done with it.
+fn accept_socket(mut req: Request<Body>, source: OverlaySource) -> Response<Body> {
+  // tungstenite checks the handshake on its own copy of the request, since it
+  // can't take the body.
+  let mut handshake: Request<()> = Request::new(());
+  *handshake.method_mut() = req.method().clone();
+  *handshake.uri_mut() = req.uri().clone();
+  *handshake.version_mut() = req.version();
+  *handshake.headers_mut() = req.headers().clone();
+
+  let accepted = match create_response(&handshake) {
+    Err(e) => {
+      debug!(error = %e, "Refused an overlay connection");
+      return Response::builder()
+        .status(400)
+        .body(Body::from(e.to_string()))
+        .expect("Expected the bad request response to be valid.");
+    },
+    Ok(accepted) => accepted
+  };
+
+  tokio::spawn(async move {
+    match hyper::upgrade::on(&mut req).await {
+      Err(e) => debug!(error = %e, "Couldn't upgrade an overlay connection"),
+      Ok(upgraded) => push_updates(upgraded, source).await
+    }
+  });
+
+  let (parts, _) = accepted.into_parts();
+  return Response::from_parts(parts, Body::empty());
+}
+
+fn respond(req: Request<Body>, source: OverlaySource) -> Response<Body> {
+  match (req.method(), req.uri().path()) {
+    (&Method::GET, "/") => return Response::builder()
+      .header("Content-Type", "text/html; charset=utf-8")
+      .body(Body::from(OVERLAY_PAGE))
+      .expect("Expected the overlay page response to be valid."),
+    (&Method::GET, "/ws") => return accept_socket(req, source),
+    _ => return not_found()
+  }
+}
+
+// Serves the overlay page at / and its updates at /ws until the bot stops.
+pub async fn serve_overlay(server: Builder<AddrIncoming>, source: OverlaySource) -> () {
+  let make_service = make_service_fn(move |_conn| {
+    let source: OverlaySource = source.clone();
+    async move {
+      return Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+        let response: Response<Body> = respond(req, source.clone());
+        async move {
+          return Ok::<_, Infallible>(response);
+        }
+      }));
+    }
+  });
+
+  let server = server.serve(make_service);
+  info!(address = %server.local_addr(), "Serving the overlay");
+  if let Err(e) = server.await {
+    error!(error = %e, "The overlay server stopped");
+  }
+}
\ No newline at end of file
@@ -0,0 +1,106 @@
+use regex::Regex;
+
+// Works out whether a message is meant for the bot, and if it is, what's left
+// of it once the prefix is taken off.
+pub struct PrefixMatcher {
+  prefix: String,
+  pattern: Option<Regex>,
+  mention: bool,
+  // Channels where every message is treated as a command, prefix or not.
+  prefixless_channels: Vec<u64>
+}
+
+impl PrefixMatcher {
+  pub fn new(prefix: String, pattern: Option<Regex>, mention: bool, prefixless_channels: Vec<u64>) -> PrefixMatcher {
+    return PrefixMatcher {
+      prefix: prefix,
+      pattern: pattern,
+      mention: mention,
+      prefixless_channels: prefixless_channels
+    }
+  }
+
+  pub fn get_prefix(&self) -> &String {
+    return &self.prefix;
+  }
+
+  // Mentions come through as "<@id>", or "<@!id>" if the bot has a nickname.
+  fn strip_mention<'a>(&self, content: &'a str, bot_id: u64) -> Option<&'a str> {
+    let rest: &str = content.strip_prefix("<@")?;
+    let rest: &str = rest.strip_prefix("!").unwrap_or(rest);
+    return rest.strip_prefix(&format!("{}>", bot_id));
+  }
+
+  fn strip_any<'a>(&self, content: &'a str, bot_id: Option<u64>) -> Option<&'a str> {
+    if !self.prefix.is_empty() {
+      if let Some(rest) = content.strip_prefix(&self.prefix) {
+        return Some(rest);
+      }
+    }
+    if let Some(pattern) = &self.pattern {
+      // Matches only count if they're right at the start of the message.
+      if let Some(found) = pattern.find(content) {
+        if found.start() == 0 {
+          return Some(&content[found.end()..]);
+        }
+      }
+    }
+    if self.mention {
+      if let Some(bot_id) = bot_id {
+        return self.strip_mention(content, bot_id);
+      }
+    }
+    return None;
+  }
+
+  pub fn strip<'a>(&self, content: &'a str, channel_id: u64, bot_id: Option<u64>) -> Option<&'a str> {
+    let stripped: Option<&str> = match self.strip_any(content, bot_id) {
+      Some(rest) => Some(rest),
+      None if self.prefixless_channels.contains(&channel_id) => Some(content),
+      None => None
+    };
+    match stripped.map(|rest| rest.trim()) {
+      None | Some("") => return None,
+      Some(rest) => return Some(rest)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn strips_the_prefix() {
+    let matcher: PrefixMatcher = PrefixMatcher::new("!".to_string(), None, false, vec![]);
+    assert_eq!(matcher.strip("!up 3", 1, None), Some("up 3"));
+    assert_eq!(matcher.strip("!  up  ", 1, None), Some("up"));
+    assert_eq!(matcher.strip("up 3", 1, None), None);
+    assert_eq!(matcher.strip("!", 1, None), None);
+  }
+
+  #[test]
+  fn pattern_only_matches_at_the_start() {
+    let pattern: Regex = Regex::new("(?i)hey bot,?").unwrap();
+    let matcher: PrefixMatcher = PrefixMatcher::new(String::new(), Some(pattern), false, vec![]);
+    assert_eq!(matcher.strip("Hey bot, jump", 1, None), Some("jump"));
+    assert_eq!(matcher.strip("so hey bot jump", 1, None), None);
+  }
+
+  #[test]
+  fn strips_mentions() {
+    let matcher: PrefixMatcher = PrefixMatcher::new("!".to_string(), None, true, vec![]);
+    assert_eq!(matcher.strip("<@42> up", 1, Some(42)), Some("up"));
+    assert_eq!(matcher.strip("<@!42> up", 1, Some(42)), Some("up"));
+    assert_eq!(matcher.strip("<@43> up", 1, Some(42)), None);
+    assert_eq!(matcher.strip("<@42> up", 1, None), None);
+  }
+
+  #[test]
+  fn prefixless_channels_take_everything() {
+    let matcher: PrefixMatcher = PrefixMatcher::new("!".to_string(), None, false, vec![7]);
+    assert_eq!(matcher.strip("up", 7, None), Some("up"));
+    assert_eq!(matcher.strip("!up", 7, None), Some("up"));
+    assert_eq!(matcher.strip("up", 8, None), None);
+  }
+}
\ No newline at end of file
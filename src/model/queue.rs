@@ -0,0 +1,124 @@
+use crate::model::key::KeyInputtable;
+use std::{sync::Arc, time::Instant};
+use tokio::sync::RwLock;
+use tracing::Span;
+
+// The input stack, shared between the bot and anything that reports on it.
+pub type SharedInputStack = Arc<RwLock<Vec<QueuedInput>>>;
+
+// An input waiting on the stack, along with what's needed to keep track of
+// where it came from.
+pub struct QueuedInput {
+  input: Box<dyn KeyInputtable + Send + Sync>,
+  // How many presses the input started out with, since the input itself
+  // counts down as it's executed.
+  presses: i8,
+  // The message exactly as it was sent, and the command it was parsed from,
+  // like "up 3".
+  message: String,
+  command: String,
+  user_id: u64,
+  user: String,
+  // Where the message came from, so that its reaction can be kept up to date.
+  channel_id: u64,
+  message_id: u64,
+  // When the input was queued, and when it was first pressed, for measuring
+  // how long it waited and how long it took.
+  queued_at: Instant,
+  started_at: Option<Instant>,
+  // The span of the message the input came from, so that executing it gets
+  // logged alongside everything else that happened to the message.
+  span: Span
+}
+
+impl QueuedInput {
+  pub fn new(
+    input: Box<dyn KeyInputtable + Send + Sync>,
+    message: String,
+    command: String,
+    user_id: u64,
+    user: String,
+    channel_id: u64,
+    message_id: u64,
+    span: Span
+  ) -> QueuedInput {
+    return QueuedInput {
+      presses: *input.get_presses(),
+      input: input,
+      message: message,
+      command: command,
+      user_id: user_id,
+      user: user,
+      channel_id: channel_id,
+      message_id: message_id,
+      queued_at: Instant::now(),
+      started_at: None,
+      span: span
+    }
+  }
+
+  pub fn get_input(&self) -> &Box<dyn KeyInputtable + Send + Sync> {
+    return &self.input;
+  }
+
+  pub fn get_presses(&self) -> &i8 {
+    return &self.presses;
+  }
+
+  pub fn get_message(&self) -> &String {
+    return &self.message;
+  }
+
+  pub fn get_command(&self) -> &String {
+    return &self.command;
+  }
+
+  pub fn get_user_id(&self) -> &u64 {
+    return &self.user_id;
+  }
+
+  pub fn get_user(&self) -> &String {
+    return &self.user;
+  }
+
+  pub fn get_channel_id(&self) -> &u64 {
+    return &self.channel_id;
+  }
+
+  pub fn get_message_id(&self) -> &u64 {
+    return &self.message_id;
+  }
+
+  pub fn get_queued_at(&self) -> &Instant {
+    return &self.queued_at;
+  }
+
+  pub fn get_started_at(&self) -> &Option<Instant> {
+    return &self.started_at;
+  }
+
+  // Negative presses are counted like positive ones, since they still get
+  // pressed that many times.
+  pub fn get_presses_left(&self) -> u8 {
+    return self.input.get_presses().unsigned_abs();
+  }
+
+  // Called right before every press, but only the first one counts.
+  pub fn start(&mut self) -> () {
+    if self.started_at.is_none() {
+      self.started_at = Some(Instant::now());
+    }
+  }
+
+  pub fn get_span(&self) -> &Span {
+    return &self.span;
+  }
+
+  // The same queued input, but with what's left of the input after a press.
+  pub fn with_input(self, input: Box<dyn KeyInputtable + Send + Sync>) -> QueuedInput {
+    return QueuedInput {
+      input: input,
+      ..self
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,77 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::error::ParseError;
+use serde::{Serialize, Deserialize};
+
+// The range of repeat counts an input is allowed to have. Some games give
+// negative counts a meaning (like depowering systems in FTL), so the range
+// isn't always positive.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct RepeatPolicy {
+  min: i8,
+  max: i8
+}
+
+impl RepeatPolicy {
+  pub fn new(min: i8, max: i8) -> RepeatPolicy {
+    return RepeatPolicy {
+      min: min,
+      max: max
+    }
+  }
+
+  pub fn get_min(&self) -> &i8 {
+    return &self.min;
+  }
+
+  pub fn get_max(&self) -> &i8 {
+    return &self.max;
+  }
+
+  pub fn check(&self, presses: i8) -> Result<(), ParseError> {
+    if presses < self.min || presses > self.max {
+      return Err(ParseError::RepeatLimit(self.min, self.max, presses as i64));
+    }
+    return Ok(());
+  }
+
+  // The same policy, minus negative counts.
+  pub fn positive_only(&self) -> RepeatPolicy {
+    return RepeatPolicy::new(self.min.max(1), self.max);
+  }
+
+  // Parsers don't know the limits, so counts that were too big to parse into
+  // an input are explained here instead, the same as any other count that's
+  // out of range.
+  pub fn explain(&self, e: ParseError) -> ParseError {
+    match e {
+      ParseError::RepeatCount(presses) => return ParseError::RepeatLimit(self.min, self.max, presses),
+      e => return e
+    }
+  }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepeatLimits {
+  ftl: RepeatPolicy,
+  nds: RepeatPolicy
+}
+
+impl RepeatLimits {
+  pub fn for_game(&self, game: &SupportedGame) -> RepeatPolicy {
+    match game {
+      SupportedGame::FTL => return self.ftl,
+      SupportedGame::NDS => return self.nds
+    }
+  }
+}
+
+impl Default for RepeatLimits {
+  fn default() -> RepeatLimits {
+    return RepeatLimits {
+      // Systems in FTL can't have more than 8 power bars anyways.
+      ftl: RepeatPolicy::new(-8, 8),
+      nds: RepeatPolicy::new(1, 20)
+    }
+  }
+}
\ No newline at end of file
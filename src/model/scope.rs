@@ -0,0 +1,42 @@
+use serde::{Serialize, Deserialize};
+
+// What to do with commands that are sent through direct messages, since they
+// don't belong to any guild or channel that could be allowlisted.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum DmPolicy {
+  Allow,
+  AdminsOnly,
+  Ignore
+}
+
+// Restricts where the bot will read commands from. An empty list of guilds or
+// channels means that any guild or channel is fine.
+pub struct CommandScope {
+  guilds: Vec<u64>,
+  channels: Vec<u64>,
+  dm_policy: DmPolicy
+}
+
+impl CommandScope {
+  pub fn new(guilds: Vec<u64>, channels: Vec<u64>, dm_policy: DmPolicy) -> CommandScope {
+    return CommandScope {
+      guilds: guilds,
+      channels: channels,
+      dm_policy: dm_policy
+    }
+  }
+
+  pub fn allows(&self, guild_id: Option<u64>, channel_id: u64, is_admin: bool) -> bool {
+    match guild_id {
+      None => match self.dm_policy {
+        DmPolicy::Allow => return true,
+        DmPolicy::AdminsOnly => return is_admin,
+        DmPolicy::Ignore => return false
+      },
+      Some(guild_id) => {
+        return (self.guilds.is_empty() || self.guilds.contains(&guild_id))
+          && (self.channels.is_empty() || self.channels.contains(&channel_id));
+      }
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+// Wraps anything that shouldn't end up in logs or error messages, like the
+// bot's token. The only way to see what's inside is to ask for it explicitly.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+  pub fn new(secret: String) -> Secret {
+    return Secret(secret);
+  }
+
+  pub fn expose(&self) -> &str {
+    return &self.0;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.0.is_empty();
+  }
+}
+
+impl fmt::Debug for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Secret([redacted])")
+  }
+}
+
+impl fmt::Display for Secret {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "[redacted]")
+  }
+}
\ No newline at end of file
@@ -0,0 +1,139 @@
+use crate::game::supported_game::SupportedGame;
+use crate::model::{
+  admin::AdminList,
+  audit::AuditLog,
+  app_model::AppModel,
+  bot::{GameFocusChecker, MessageParser},
+  focus_loss::FocusLoss,
+  input_backend::InputBackend,
+  prefix::PrefixMatcher,
+  repeat_policy::RepeatPolicy,
+  scope::CommandScope,
+  viewer::QueueFeedback
+};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Everything the bot needs from the config once it's running. These are built
+// all at once and swapped out all at once, so a reload can never leave the bot
+// with, say, FTL's parser and the DS's focus checker.
+pub struct BotSettings {
+  // Whatever these settings were built from, so that they can be rebuilt with
+  // a change or two.
+  config: Box<dyn AppModel>,
+  prefix: PrefixMatcher,
+  game: SupportedGame,
+  msg_parser: Box<dyn MessageParser + Send + Sync>,
+  repeat_policy: RepeatPolicy,
+  focus_checker: Box<dyn GameFocusChecker + Send + Sync>,
+  focus_loss: FocusLoss,
+  backend: Box<dyn InputBackend + Send + Sync>,
+  admins: AdminList,
+  scope: CommandScope,
+  // Where status updates like losing focus are announced. Without one, they
+  // go to wherever the last input came from.
+  status_channel: Option<u64>,
+  // Opening it starts a writer thread, so it's opened once and handed to
+  // every rebuild.
+  audit_log: Option<Arc<AuditLog>>,
+  queue_feedback: QueueFeedback,
+  // How many inputs can be queued before the oldest start getting dropped, or
+  // 0 for no limit.
+  max_queue: usize
+}
+
+// Readers clone the inner Arc and let go of the lock right away, so swapping in
+// new settings never has to wait on a long-running input.
+pub type SharedSettings = Arc<RwLock<Arc<BotSettings>>>;
+
+impl BotSettings {
+  pub fn new(
+    config: Box<dyn AppModel>,
+    prefix: PrefixMatcher,
+    game: SupportedGame,
+    msg_parser: Box<dyn MessageParser + Send + Sync>,
+    repeat_policy: RepeatPolicy,
+    focus_checker: Box<dyn GameFocusChecker + Send + Sync>,
+    focus_loss: FocusLoss,
+    backend: Box<dyn InputBackend + Send + Sync>,
+    admins: AdminList,
+    scope: CommandScope,
+    status_channel: Option<u64>,
+    audit_log: Option<Arc<AuditLog>>,
+    queue_feedback: QueueFeedback,
+    max_queue: usize
+  ) -> BotSettings {
+    return BotSettings {
+      config: config,
+      prefix: prefix,
+      game: game,
+      msg_parser: msg_parser,
+      repeat_policy: repeat_policy,
+      focus_checker: focus_checker,
+      focus_loss: focus_loss,
+      backend: backend,
+      admins: admins,
+      scope: scope,
+      status_channel: status_channel,
+      audit_log: audit_log,
+      queue_feedback: queue_feedback,
+      max_queue: max_queue
+    }
+  }
+
+  pub fn get_config(&self) -> &Box<dyn AppModel> {
+    return &self.config;
+  }
+
+  pub fn get_prefix(&self) -> &PrefixMatcher {
+    return &self.prefix;
+  }
+
+  pub fn get_game(&self) -> &SupportedGame {
+    return &self.game;
+  }
+
+  pub fn get_msg_parser(&self) -> &Box<dyn MessageParser + Send + Sync> {
+    return &self.msg_parser;
+  }
+
+  pub fn get_repeat_policy(&self) -> &RepeatPolicy {
+    return &self.repeat_policy;
+  }
+
+  pub fn get_focus_checker(&self) -> &Box<dyn GameFocusChecker + Send + Sync> {
+    return &self.focus_checker;
+  }
+
+  pub fn get_focus_loss(&self) -> &FocusLoss {
+    return &self.focus_loss;
+  }
+
+  pub fn get_backend(&self) -> &Box<dyn InputBackend + Send + Sync> {
+    return &self.backend;
+  }
+
+  pub fn get_admins(&self) -> &AdminList {
+    return &self.admins;
+  }
+
+  pub fn get_scope(&self) -> &CommandScope {
+    return &self.scope;
+  }
+
+  pub fn get_status_channel(&self) -> &Option<u64> {
+    return &self.status_channel;
+  }
+
+  pub fn get_audit_log(&self) -> &Option<Arc<AuditLog>> {
+    return &self.audit_log;
+  }
+
+  pub fn get_queue_feedback(&self) -> &QueueFeedback {
+    return &self.queue_feedback;
+  }
+
+  pub fn get_max_queue(&self) -> &usize {
+    return &self.max_queue;
+  }
+}
\ No newline at end of file
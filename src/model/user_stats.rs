@@ -0,0 +1,153 @@
+use confy::ConfyError;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+// How many users the leaderboard shows, and how many favorite actions are
+// shown for each user.
+pub const LEADERBOARD_SIZE: usize = 10;
+const FAVORITE_ACTIONS: usize = 3;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserStats {
+  user: u64,
+  // Whatever the user was called the last time they sent something, so that
+  // the leaderboard doesn't have to mention anyone.
+  name: String,
+  inputs: u64,
+  // Streaks count days in a row (in UTC) that the user had an input executed.
+  // The day is counted in days since the Unix epoch.
+  current_streak: u64,
+  best_streak: u64,
+  last_day: u64,
+  // How many times each action was executed, by the command's name. This has
+  // to stay last, since it's written as a table.
+  actions: HashMap<String, u64>
+}
+
+impl UserStats {
+  fn new(user: u64, name: &str) -> UserStats {
+    return UserStats {
+      user: user,
+      name: name.to_string(),
+      inputs: 0,
+      current_streak: 0,
+      best_streak: 0,
+      last_day: 0,
+      actions: HashMap::new()
+    }
+  }
+
+  pub fn get_name(&self) -> &String {
+    return &self.name;
+  }
+
+  pub fn get_inputs(&self) -> &u64 {
+    return &self.inputs;
+  }
+
+  pub fn get_best_streak(&self) -> &u64 {
+    return &self.best_streak;
+  }
+
+  // A streak that missed yesterday is already over, even if nothing's been
+  // executed since to reset it.
+  pub fn current_streak(&self, now: u64) -> u64 {
+    match now / SECONDS_PER_DAY <= self.last_day + 1 {
+      true => return self.current_streak,
+      false => return 0
+    }
+  }
+
+  // The most executed actions, most executed first. Ties go alphabetically,
+  // so the same stats always read the same way.
+  pub fn favorite_actions(&self) -> Vec<(&String, &u64)> {
+    let mut actions: Vec<(&String, &u64)> = self.actions.iter().collect();
+    actions.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    actions.truncate(FAVORITE_ACTIONS);
+    return actions;
+  }
+}
+
+// Everyone who's ever had an input executed, saved between runs the same way as
+// the ban list.
+#[derive(Default, Serialize, Deserialize)]
+pub struct StatsList {
+  #[serde(skip)]
+  path: String,
+  // Whether anything's changed since the list was last saved.
+  #[serde(skip)]
+  dirty: bool,
+  users: Vec<UserStats>
+}
+
+impl StatsList {
+  pub fn load(path: &str) -> Result<StatsList, ConfyError> {
+    let mut stats_list: StatsList = confy::load_path(path)?;
+    stats_list.path = path.to_string();
+    return Ok(stats_list);
+  }
+
+  // A copy of the list to save, but only if something's changed since the
+  // last copy was taken. Saving the copy can then happen without holding up
+  // anything that wants the list.
+  pub fn take_changes(&mut self) -> Option<StatsList> {
+    if !self.dirty {
+      return None;
+    }
+    self.dirty = false;
+    return Some(StatsList {
+      path: self.path.to_string(),
+      dirty: false,
+      users: self.users.clone()
+    });
+  }
+
+  // For when saving a copy didn't work out, so that the next save tries again.
+  pub fn mark_changed(&mut self) -> () {
+    self.dirty = true;
+  }
+
+  pub fn store(&self) -> Result<(), ConfyError> {
+    return confy::store_path(&self.path, self);
+  }
+
+  // Only the command's name counts as the action, so "up 3" and "up" are the
+  // same thing.
+  pub fn record(&mut self, user: u64, name: &str, command: &str, now: u64) -> () {
+    let action: String = command.split_whitespace().next().unwrap_or("").to_string();
+    let today: u64 = now / SECONDS_PER_DAY;
+    let stats: &mut UserStats = match self.users.iter().position(|stats| stats.user == user) {
+      Some(i) => &mut self.users[i],
+      None => {
+        self.users.push(UserStats::new(user, name));
+        self.users.last_mut().expect("Expected the user that was just added to be there.")
+      }
+    };
+
+    stats.name = name.to_string();
+    stats.inputs += 1;
+    *stats.actions.entry(action).or_insert(0) += 1;
+    if stats.current_streak == 0 || today > stats.last_day + 1 {
+      stats.current_streak = 1;
+    } else if today == stats.last_day + 1 {
+      stats.current_streak += 1;
+    }
+    stats.last_day = today;
+    stats.best_streak = std::cmp::max(stats.best_streak, stats.current_streak);
+    self.dirty = true;
+  }
+
+  pub fn get(&self, user: u64) -> Option<&UserStats> {
+    return self.users.iter().find(|stats| stats.user == user);
+  }
+
+  // Whoever's had the most inputs executed, most first.
+  pub fn leaderboard(&self) -> Vec<&UserStats> {
+    let mut users: Vec<&UserStats> = self.users.iter().collect();
+    users.sort_by(|a, b| b.inputs.cmp(&a.inputs));
+    users.truncate(LEADERBOARD_SIZE);
+    return users;
+  }
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+// Commands that anyone can use to see what's going on. Like admin commands,
+// these are checked before the game's parser, so none of these names can double
+// as game inputs.
+pub enum ViewerCommand {
+  Queue,
+  Stats,
+  Leaderboard
+}
+
+impl FromStr for ViewerCommand {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<ViewerCommand, Self::Err> {
+    match s {
+      "queue" => return Ok(ViewerCommand::Queue),
+      "stats" => return Ok(ViewerCommand::Stats),
+      "leaderboard" => return Ok(ViewerCommand::Leaderboard),
+      _ => return Err(())
+    }
+  }
+}
+
+// How someone finds out where their input landed in the queue.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum QueueFeedback {
+  // Nothing past the usual reaction.
+  Off,
+  // A number reaction with their position, for the first 10 positions.
+  Reaction,
+  // A reply with their position and about how long until it's executed.
+  Reply
+}
+
+// The keycap emoji for a queue position, if there is one.
+pub fn position_emoji(position: usize) -> Option<&'static str> {
+  const KEYCAPS: [&str; 10] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣", "🔟"];
+  return KEYCAPS.get(position.wrapping_sub(1)).copied();
+}
\ No newline at end of file
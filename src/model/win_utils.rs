@@ -1,6 +1,24 @@
+use inputbot::KeybdKey;
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
-use winapi::um::winuser::{GetForegroundWindow, GetWindowTextW};
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::OpenProcess;
+use winapi::um::winbase::QueryFullProcessImageNameW;
+use winapi::um::winnt::{HANDLE, PROCESS_QUERY_LIMITED_INFORMATION};
+use winapi::um::winuser::{
+  EnumWindows,
+  GetForegroundWindow,
+  GetWindowTextW,
+  GetWindowThreadProcessId,
+  IsWindowVisible,
+  MapVirtualKeyW,
+  PostMessageW,
+  MAPVK_VK_TO_VSC,
+  WM_KEYDOWN,
+  WM_KEYUP
+};
 
 // If I didn't find the StackOverflow posts for these two functions, I don't
 // think there's any way I would have been able to do something as simple as
@@ -15,13 +33,93 @@ unsafe fn u16_ptr_to_string(ptr: *const u16) -> OsString {
   return OsString::from_wide(slice);
 }
 
+// Windows are passed around as plain numbers so that the rest of the bot
+// doesn't need to know what a window handle looks like on each platform.
+pub fn get_focused_window() -> Option<u64> {
+  let window: HWND = unsafe { GetForegroundWindow() };
+  match window.is_null() {
+    true => return None,
+    false => return Some(window as u64)
+  }
+}
+
+unsafe extern "system" fn collect_window(window: HWND, windows: LPARAM) -> BOOL {
+  if IsWindowVisible(window) != FALSE {
+    (*(windows as *mut Vec<u64>)).push(window as u64);
+  }
+  return TRUE;
+}
+
+// Every visible top-level window.
+pub fn get_windows() -> Vec<u64> {
+  let mut windows: Vec<u64> = Vec::new();
+
+  unsafe {
+    EnumWindows(Some(collect_window), &mut windows as *mut Vec<u64> as LPARAM);
+  };
+  return windows;
+}
+
 // Effectively copied from https://stackoverflow.com/q/54962557
-pub fn get_focused_window() -> OsString {
-  let name: Vec<u16> = Vec::with_capacity(1024);
-  let ptr: *const u16 = name.as_ptr();
+pub fn get_window_title(window: u64) -> Option<OsString> {
+  let mut name: Vec<u16> = vec![0; 1024];
+
+  unsafe {
+    GetWindowTextW(window as HWND, name.as_mut_ptr(), name.len() as i32);
+    return Some(u16_ptr_to_string(name.as_ptr()));
+  };
+}
+
+pub fn get_window_process_id(window: u64) -> Option<u32> {
+  let mut pid: DWORD = 0;
+
+  unsafe {
+    GetWindowThreadProcessId(window as HWND, &mut pid);
+  };
+  match pid {
+    0 => return None,
+    pid => return Some(pid)
+  }
+}
+
+// The full path of a process' executable. Only needs limited access, so it
+// works on most processes that aren't running as another user.
+pub fn get_process_image(pid: u32) -> Option<OsString> {
+  let mut name: Vec<u16> = vec![0; 1024];
+  let mut len: DWORD = name.len() as DWORD;
+
+  unsafe {
+    let process: HANDLE = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+    if process.is_null() {
+      return None;
+    }
+    let found: BOOL = QueryFullProcessImageNameW(process, 0, name.as_mut_ptr(), &mut len);
+    CloseHandle(process);
+    if found == FALSE {
+      return None;
+    }
+  };
+  return Some(OsString::from_wide(&name[..len as usize]));
+}
+
+// Posts a key event straight to a window's message queue, whether or not it
+// has focus. Games that read the keyboard directly never see these. Neither do
+// games that check whether Shift or Control is held, since posted messages
+// don't change the keyboard's state, so there's nothing to do with `held`.
+// For that reason, FTL's repeat limits get `RepeatPolicy::positive_only()` in
+// `AppModelSerenity::build_settings` whenever the backend can't hold modifiers,
+// so depowering is rejected instead of powering systems up.
+pub fn send_key(window: u64, key: KeybdKey, pressed: bool, _held: &[KeybdKey]) -> () {
+  // inputbot already knows each key's virtual key code.
+  let code: UINT = u64::from(key) as UINT;
 
   unsafe {
-    GetWindowTextW(GetForegroundWindow(), ptr as *mut u16, 1024);
-    return u16_ptr_to_string(ptr);
+    let scan_code: UINT = MapVirtualKeyW(code, MAPVK_VK_TO_VSC);
+    // The low bits are the repeat count, and the top two mark a key release.
+    let (message, flags): (UINT, UINT) = match pressed {
+      true => (WM_KEYDOWN, 1),
+      false => (WM_KEYUP, 1 | 0xC000_0000)
+    };
+    PostMessageW(window as HWND, message, code as WPARAM, (flags | (scan_code << 16)) as LPARAM);
   };
 }
\ No newline at end of file
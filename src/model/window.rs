@@ -0,0 +1,50 @@
+#[cfg(windows)]
+use crate::model::win_utils as platform;
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::model::x11_utils as platform;
+use inputbot::KeybdKey;
+use std::{ffi::OsString, path::Path};
+
+// A top-level window on whatever platform the bot is running on. The ID is a
+// window handle on Windows, or a window ID on X11.
+#[derive(Copy, Clone)]
+pub struct Window {
+  id: u64
+}
+
+impl Window {
+  pub fn focused() -> Option<Window> {
+    return platform::get_focused_window().map(|id| Window { id: id });
+  }
+
+  // The first window that matches, if any do.
+  pub fn find(matches: impl Fn(&Window) -> bool) -> Option<Window> {
+    return platform::get_windows()
+      .into_iter()
+      .map(|id| Window { id: id })
+      .find(|window| matches(window));
+  }
+
+  // The window's title, if it's valid unicode.
+  pub fn title(&self) -> Option<String> {
+    return platform::get_window_title(self.id).and_then(|title| title.into_string().ok());
+  }
+
+  pub fn pid(&self) -> Option<u32> {
+    return platform::get_window_process_id(self.id);
+  }
+
+  // The file name of the window's executable, without the path.
+  pub fn executable(&self) -> Option<String> {
+    let image: OsString = platform::get_process_image(self.pid()?)?;
+    return Path::new(&image).file_name()
+      .and_then(|file| file.to_str())
+      .map(|file| file.to_string());
+  }
+
+  // `held` is every modifier key that's already being held down, since the
+  // window has no other way of knowing about them.
+  pub fn send_key(&self, key: KeybdKey, pressed: bool, held: &[KeybdKey]) -> () {
+    platform::send_key(self.id, key, pressed, held);
+  }
+}
\ No newline at end of file
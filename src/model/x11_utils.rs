@@ -0,0 +1,180 @@
+use inputbot::KeybdKey;
+use std::ffi::{CStr, CString, OsString};
+use std::os::raw::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
+use std::{fs, ptr, slice};
+use std::sync::{Mutex, MutexGuard};
+use x11::xlib;
+
+// Opening a connection to the X server for every check would be slow, since
+// focus gets checked constantly, so one connection is kept around. Xlib isn't
+// thread safe on its own, hence the lock.
+struct Connection(*mut xlib::Display);
+
+unsafe impl Send for Connection {}
+
+static CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+
+// Runs something with the display, connecting first if this is the first time.
+// Nothing is run if there's no X server to connect to.
+fn with_display<T>(f: impl FnOnce(*mut xlib::Display) -> Option<T>) -> Option<T> {
+  let mut connection: MutexGuard<Option<Connection>> = CONNECTION.lock()
+    .expect("Expected the X connection's lock to not be poisoned.");
+  if connection.is_none() {
+    let display: *mut xlib::Display = unsafe { xlib::XOpenDisplay(ptr::null()) };
+    if display.is_null() {
+      return None;
+    }
+    *connection = Some(Connection(display));
+  }
+  return connection.as_ref().and_then(|connection| f(connection.0));
+}
+
+unsafe fn intern_atom(display: *mut xlib::Display, name: &str) -> xlib::Atom {
+  let name: CString = CString::new(name).expect("Expected atom names to not contain nul bytes.");
+  return xlib::XInternAtom(display, name.as_ptr(), xlib::False);
+}
+
+// Reads a window property as raw bytes, along with its format (8, 16 or 32).
+// 32-bit properties come back as longs, whatever size those happen to be.
+unsafe fn get_property(display: *mut xlib::Display, window: c_ulong, property: &str, kind: xlib::Atom)
+  -> Option<(Vec<u8>, c_int, usize)> {
+  let mut actual_kind: xlib::Atom = 0;
+  let mut format: c_int = 0;
+  let mut count: c_ulong = 0;
+  let mut remaining: c_ulong = 0;
+  let mut data: *mut c_uchar = ptr::null_mut();
+
+  let status: c_int = xlib::XGetWindowProperty(
+    display, window, intern_atom(display, property), 0, c_long::MAX, xlib::False, kind,
+    &mut actual_kind, &mut format, &mut count, &mut remaining, &mut data
+  );
+  if status != xlib::Success as c_int || data.is_null() {
+    return None;
+  }
+  let item_size: usize = match format {
+    8 => 1,
+    16 => std::mem::size_of::<i16>(),
+    _ => std::mem::size_of::<c_long>()
+  };
+  let bytes: Vec<u8> = slice::from_raw_parts(data, count as usize * item_size).to_vec();
+  xlib::XFree(data as *mut _);
+  if actual_kind == 0 {
+    return None;
+  }
+  return Some((bytes, format, count as usize));
+}
+
+unsafe fn get_longs(display: *mut xlib::Display, window: c_ulong, property: &str, kind: xlib::Atom)
+  -> Vec<c_ulong> {
+  match get_property(display, window, property, kind) {
+    Some((bytes, 32, count)) => return (0..count)
+      .map(|i| ptr::read_unaligned((bytes.as_ptr() as *const c_ulong).add(i)))
+      .collect(),
+    _ => return Vec::new()
+  }
+}
+
+// The focused window, going by the window manager. Falls back to whatever has
+// keyboard focus if the window manager doesn't say.
+pub fn get_focused_window() -> Option<u64> {
+  return with_display(|display| unsafe {
+    let root: c_ulong = xlib::XDefaultRootWindow(display);
+    if let Some(window) = get_longs(display, root, "_NET_ACTIVE_WINDOW", xlib::XA_WINDOW).first() {
+      if *window != 0 {
+        return Some(*window as u64);
+      }
+    }
+    let mut window: c_ulong = 0;
+    let mut revert_to: c_int = 0;
+    xlib::XGetInputFocus(display, &mut window, &mut revert_to);
+    match window {
+      0 | 1 => return None,
+      window => return Some(window as u64)
+    }
+  });
+}
+
+// Every window the window manager is managing, which leaves out all of the
+// invisible helper windows that applications tend to make.
+pub fn get_windows() -> Vec<u64> {
+  return with_display(|display| unsafe {
+    let root: c_ulong = xlib::XDefaultRootWindow(display);
+    return Some(get_longs(display, root, "_NET_CLIENT_LIST", xlib::XA_WINDOW)
+      .into_iter()
+      .map(|window| window as u64)
+      .collect());
+  }).unwrap_or(Vec::new());
+}
+
+pub fn get_window_title(window: u64) -> Option<OsString> {
+  return with_display(|display| unsafe {
+    let utf8: xlib::Atom = intern_atom(display, "UTF8_STRING");
+    if let Some((bytes, 8, _)) = get_property(display, window as c_ulong, "_NET_WM_NAME", utf8) {
+      return Some(OsString::from(String::from_utf8_lossy(&bytes).into_owned()));
+    }
+    let mut name: *mut c_char = ptr::null_mut();
+    if xlib::XFetchName(display, window as c_ulong, &mut name) == 0 || name.is_null() {
+      return None;
+    }
+    let title: String = CStr::from_ptr(name).to_string_lossy().into_owned();
+    xlib::XFree(name as *mut _);
+    return Some(OsString::from(title));
+  });
+}
+
+pub fn get_window_process_id(window: u64) -> Option<u32> {
+  return with_display(|display| unsafe {
+    return get_longs(display, window as c_ulong, "_NET_WM_PID", xlib::XA_CARDINAL)
+      .first()
+      .map(|pid| *pid as u32);
+  });
+}
+
+pub fn get_process_image(pid: u32) -> Option<OsString> {
+  return fs::read_link(format!("/proc/{}/exe", pid))
+    .ok()
+    .map(|path| path.into_os_string());
+}
+
+// Events sent to a window don't go through the X server's keyboard state, so
+// whichever modifiers are held have to be spelled out in each event.
+fn modifier_state(held: &[KeybdKey]) -> c_uint {
+  return held.iter().fold(0, |state, key| state | match key {
+    KeybdKey::LShiftKey | KeybdKey::RShiftKey => xlib::ShiftMask,
+    KeybdKey::LControlKey | KeybdKey::RControlKey => xlib::ControlMask,
+    _ => 0
+  });
+}
+
+// Sends a key event straight to a window, whether or not it has focus. Events
+// sent this way are marked as synthetic, and a few programs ignore those.
+pub fn send_key(window: u64, key: KeybdKey, pressed: bool, held: &[KeybdKey]) -> () {
+  with_display(|display| unsafe {
+    let (kind, mask): (c_int, c_long) = match pressed {
+      true => (xlib::KeyPress, xlib::KeyPressMask),
+      false => (xlib::KeyRelease, xlib::KeyReleaseMask)
+    };
+    // inputbot already knows each key's keysym.
+    let keycode: c_uchar = xlib::XKeysymToKeycode(display, u64::from(key) as c_ulong);
+    let mut event: xlib::XEvent = xlib::XEvent::from(xlib::XKeyEvent {
+      type_: kind,
+      serial: 0,
+      send_event: xlib::True,
+      display: display,
+      window: window as c_ulong,
+      root: xlib::XDefaultRootWindow(display),
+      subwindow: 0,
+      time: xlib::CurrentTime,
+      x: 1,
+      y: 1,
+      x_root: 1,
+      y_root: 1,
+      state: modifier_state(held),
+      keycode: keycode as u32,
+      same_screen: xlib::True
+    });
+    xlib::XSendEvent(display, window as c_ulong, xlib::True, mask, &mut event);
+    xlib::XFlush(display);
+    return Some(());
+  });
+}
\ No newline at end of file
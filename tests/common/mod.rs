@@ -0,0 +1,275 @@
+// Everything the end-to-end tests need to run the dispatcher without Discord, a
+// keyboard or a game: a chat that writes down everything said to it, a backend
+// that writes down every key, and a focus checker that's flipped by hand.
+use crowdplay_bot_rs::game::{
+  ftl::FTLUtils,
+  nds::NDSUtils,
+  supported_game::SupportedGame
+};
+use crowdplay_bot_rs::model::{
+  admin::AdminList,
+  app_model::AppModelSerenity,
+  ban_list::BanList,
+  bot::{GameFocusChecker, MessageParser},
+  chat::{Chat, ChatMessage, StatusUpdate},
+  dispatcher::Dispatcher,
+  focus_loss::{FocusLoss, FocusLossPolicy},
+  input_backend::InputBackend,
+  overlay::Overlay,
+  prefix::PrefixMatcher,
+  repeat_policy::RepeatLimits,
+  scope::{CommandScope, DmPolicy},
+  settings::BotSettings,
+  user_stats::StatsList,
+  viewer::QueueFeedback
+};
+use inputbot::KeybdKey;
+use serenity::async_trait;
+use std::{
+  fs,
+  path::PathBuf,
+  process,
+  sync::{
+    Arc,
+    Mutex,
+    atomic::{AtomicBool, AtomicU64, Ordering}
+  },
+  time::{Duration, Instant}
+};
+
+pub const CHANNEL: u64 = 100;
+pub const ADMIN: u64 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum KeyEvent {
+  Press(KeybdKey),
+  Release(KeybdKey)
+}
+
+pub struct RecordingBackend {
+  events: Arc<Mutex<Vec<KeyEvent>>>
+}
+
+impl InputBackend for RecordingBackend {
+  fn press(&self, key: KeybdKey) -> () {
+    self.events.lock().unwrap().push(KeyEvent::Press(key));
+  }
+
+  fn release(&self, key: KeybdKey) -> () {
+    self.events.lock().unwrap().push(KeyEvent::Release(key));
+  }
+}
+
+pub struct FakeFocusChecker {
+  focused: Arc<AtomicBool>
+}
+
+impl GameFocusChecker for FakeFocusChecker {
+  fn game_focused(&self) -> bool {
+    return self.focused.load(Ordering::Relaxed);
+  }
+}
+
+// What happened to a message, in the order it happened. Statuses are kept as
+// their emoji, since that's what chat would end up showing.
+#[derive(Debug, PartialEq)]
+pub enum ChatEvent {
+  Said(String),
+  Replied(u64, String),
+  Reacted(u64, String),
+  Status(u64, &'static str)
+}
+
+#[derive(Default)]
+pub struct MockChat {
+  events: Mutex<Vec<ChatEvent>>
+}
+
+impl MockChat {
+  pub fn events(&self) -> Vec<ChatEvent> {
+    return self.events.lock().unwrap().drain(..).collect();
+  }
+
+  fn record(&self, event: ChatEvent) -> () {
+    self.events.lock().unwrap().push(event);
+  }
+}
+
+#[async_trait]
+impl Chat for MockChat {
+  async fn say(&self, _channel_id: u64, content: &str) -> () {
+    self.record(ChatEvent::Said(content.to_string()));
+  }
+
+  async fn reply(&self, msg: &ChatMessage, content: &str) -> () {
+    self.record(ChatEvent::Replied(*msg.get_id(), content.to_string()));
+  }
+
+  async fn react(&self, msg: &ChatMessage, emoji: &str) -> () {
+    self.record(ChatEvent::Reacted(*msg.get_id(), emoji.to_string()));
+  }
+
+  fn update_status(&self, update: StatusUpdate) -> () {
+    self.record(ChatEvent::Status(update.message_id, update.status.emoji()));
+  }
+
+  async fn set_activity(&self, _activity: &str) -> () {}
+
+  async fn bot_user_id(&self) -> Option<u64> {
+    return None;
+  }
+}
+
+pub struct Options {
+  pub game: SupportedGame,
+  pub max_queue: usize,
+  pub focus_loss: FocusLossPolicy,
+  pub focused: bool,
+  pub queue_feedback: QueueFeedback
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    return Options {
+      game: SupportedGame::NDS,
+      max_queue: 0,
+      focus_loss: FocusLossPolicy::Hold,
+      focused: true,
+      queue_feedback: QueueFeedback::Off
+    }
+  }
+}
+
+// Ban and stats files have to go somewhere, so each harness gets its own
+// directory that's cleaned up afterwards.
+fn temp_dir() -> PathBuf {
+  static NEXT: AtomicU64 = AtomicU64::new(0);
+  let dir: PathBuf = std::env::temp_dir().join(format!(
+    "crowdplay-test-{}-{}",
+    process::id(),
+    NEXT.fetch_add(1, Ordering::Relaxed)
+  ));
+  fs::create_dir_all(&dir).unwrap();
+  return dir;
+}
+
+pub struct Harness {
+  pub dispatcher: Arc<Dispatcher>,
+  pub chat: Arc<MockChat>,
+  keys: Arc<Mutex<Vec<KeyEvent>>>,
+  focused: Arc<AtomicBool>,
+  next_id: AtomicU64,
+  dir: PathBuf
+}
+
+impl Harness {
+  pub fn new(options: Options) -> Harness {
+    let dir: PathBuf = temp_dir();
+    let keys: Arc<Mutex<Vec<KeyEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let focused: Arc<AtomicBool> = Arc::new(AtomicBool::new(options.focused));
+    let msg_parser: Box<dyn MessageParser + Send + Sync> = match options.game {
+      SupportedGame::FTL => Box::new(FTLUtils),
+      SupportedGame::NDS => Box::new(NDSUtils)
+    };
+
+    let settings: BotSettings = BotSettings::new(
+      Box::new(AppModelSerenity::new("!".to_string(), None, Some(options.game))),
+      PrefixMatcher::new("!".to_string(), None, false, Vec::new()),
+      options.game,
+      msg_parser,
+      RepeatLimits::default().for_game(&options.game),
+      Box::new(FakeFocusChecker { focused: Arc::clone(&focused) }),
+      FocusLoss::new(options.focus_loss, 0),
+      Box::new(RecordingBackend { events: Arc::clone(&keys) }),
+      AdminList::new(Vec::new(), vec![ADMIN]),
+      CommandScope::new(Vec::new(), Vec::new(), DmPolicy::Allow),
+      None,
+      None,
+      options.queue_feedback,
+      options.max_queue
+    );
+    let dispatcher: Dispatcher = Dispatcher::new(
+      settings,
+      BanList::load(dir.join("bans.toml").to_str().unwrap()).unwrap(),
+      StatsList::load(dir.join("stats.toml").to_str().unwrap()).unwrap(),
+      None,
+      None,
+      Overlay::new(10),
+      None
+    );
+
+    return Harness {
+      dispatcher: Arc::new(dispatcher),
+      chat: Arc::new(MockChat::default()),
+      keys: keys,
+      focused: focused,
+      next_id: AtomicU64::new(1),
+      dir: dir
+    }
+  }
+
+  // Starts pressing inputs. Anything sent before this just sits in the queue,
+  // which makes it easy to line several inputs up first.
+  pub fn start(&self) -> () {
+    self.dispatcher.start(Arc::clone(&self.chat) as Arc<dyn Chat + Send + Sync>);
+  }
+
+  // Sends a message as the given user, returning the message's ID.
+  pub async fn send(&self, user_id: u64, content: &str) -> u64 {
+    let id: u64 = self.next_id.fetch_add(1, Ordering::Relaxed);
+    let msg: ChatMessage = ChatMessage::new(
+      id,
+      CHANNEL,
+      user_id,
+      format!("user{}", user_id),
+      user_id == ADMIN,
+      content.to_string()
+    );
+    self.dispatcher.handle_message(&*self.chat, msg).await;
+    return id;
+  }
+
+  // Writes a console script into the harness' directory, returning its path.
+  pub fn script(&self, lines: &str) -> String {
+    let path: PathBuf = self.dir.join("script.txt");
+    fs::write(&path, lines).unwrap();
+    return path.to_str().unwrap().to_string();
+  }
+
+  pub fn set_focused(&self, focused: bool) -> () {
+    self.focused.store(focused, Ordering::Relaxed);
+  }
+
+  pub async fn queued(&self) -> usize {
+    return self.dispatcher.get_input_stack().read().await.len();
+  }
+
+  // Waits for the queue to empty out, failing the test if it takes too long.
+  pub async fn wait_until_idle(&self) -> () {
+    let started: Instant = Instant::now();
+    while self.queued().await > 0 {
+      assert!(started.elapsed() < Duration::from_secs(10), "The queue never emptied.");
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  }
+
+  pub fn keys(&self) -> Vec<KeyEvent> {
+    return self.keys.lock().unwrap().drain(..).collect();
+  }
+}
+
+impl Drop for Harness {
+  fn drop(&mut self) -> () {
+    let _ = fs::remove_dir_all(&self.dir);
+  }
+}
+
+// Every key in the events, pressed and released once for each press.
+pub fn taps(keys: &[KeybdKey], presses: usize) -> Vec<KeyEvent> {
+  let mut events: Vec<KeyEvent> = Vec::new();
+  for _ in 0..presses {
+    events.extend(keys.iter().map(|key| KeyEvent::Press(*key)));
+    events.extend(keys.iter().map(|key| KeyEvent::Release(*key)));
+  }
+  return events;
+}
\ No newline at end of file
@@ -0,0 +1,219 @@
+mod common;
+
+use common::{ADMIN, ChatEvent, Harness, KeyEvent, Options, taps};
+use crowdplay_bot_rs::game::supported_game::SupportedGame;
+use crowdplay_bot_rs::model::{
+  bot::Bot,
+  console::BotConsole,
+  focus_loss::FocusLossPolicy,
+  viewer::QueueFeedback
+};
+use inputbot::KeybdKey::*;
+use std::{sync::Arc, time::Duration};
+
+const VIEWER: u64 = 2;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nds_repeat_counts_press_that_many_times() {
+  let harness: Harness = Harness::new(Options::default());
+  harness.send(VIEWER, "!a 3").await;
+  harness.send(VIEWER, "!up").await;
+  harness.start();
+  harness.wait_until_idle().await;
+
+  let mut expected: Vec<KeyEvent> = taps(&[XKey], 3);
+  expected.extend(taps(&[UpKey], 1));
+  assert_eq!(harness.keys(), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nds_repeat_counts_past_the_limit_are_rejected() {
+  let harness: Harness = Harness::new(Options::default());
+  let id: u64 = harness.send(VIEWER, "!a 21").await;
+
+  let events: Vec<ChatEvent> = harness.chat.events();
+  assert_eq!(events[0], ChatEvent::Status(id, "❌"));
+  assert!(matches!(&events[1], ChatEvent::Replied(reply_id, _) if *reply_id == id));
+  assert_eq!(events.len(), 2);
+  assert_eq!(harness.queued().await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nds_repeat_counts_too_big_to_hold_are_rejected() {
+  let harness: Harness = Harness::new(Options::default());
+  let id: u64 = harness.send(VIEWER, "!up 99999").await;
+
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Status(id, "❌"),
+    ChatEvent::Replied(id, "Repeat counts have to be between 1 and 20, but got 99999.".to_string())
+  ]);
+  assert_eq!(harness.queued().await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ftl_depower_holds_shift() {
+  let harness: Harness = Harness::new(Options { game: SupportedGame::FTL, ..Options::default() });
+  harness.send(VIEWER, "!shields -2").await;
+  harness.send(VIEWER, "!shields 1").await;
+  harness.start();
+  harness.wait_until_idle().await;
+
+  let mut expected: Vec<KeyEvent> = taps(&[LShiftKey, AKey], 2);
+  expected.extend(taps(&[AKey], 1));
+  assert_eq!(harness.keys(), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ftl_power_past_the_limit_is_rejected() {
+  let harness: Harness = Harness::new(Options { game: SupportedGame::FTL, ..Options::default() });
+  let id: u64 = harness.send(VIEWER, "!shields -9").await;
+
+  assert_eq!(harness.chat.events()[0], ChatEvent::Status(id, "❌"));
+  assert_eq!(harness.queued().await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inputs_are_executed_in_the_order_they_were_sent() {
+  let harness: Harness = Harness::new(Options::default());
+  let first: u64 = harness.send(VIEWER, "!a").await;
+  let second: u64 = harness.send(ADMIN, "!b").await;
+  let third: u64 = harness.send(VIEWER, "!up").await;
+  harness.start();
+  harness.wait_until_idle().await;
+
+  let mut expected: Vec<KeyEvent> = taps(&[XKey], 1);
+  expected.extend(taps(&[ZKey], 1));
+  expected.extend(taps(&[UpKey], 1));
+  assert_eq!(harness.keys(), expected);
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Status(first, "⏳"),
+    ChatEvent::Status(second, "⏳"),
+    ChatEvent::Status(third, "⏳"),
+    ChatEvent::Status(first, "✅"),
+    ChatEvent::Status(second, "✅"),
+    ChatEvent::Status(third, "✅")
+  ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_full_queue_drops_the_oldest_input() {
+  let harness: Harness = Harness::new(Options { max_queue: 2, ..Options::default() });
+  let first: u64 = harness.send(VIEWER, "!a").await;
+  let second: u64 = harness.send(VIEWER, "!b").await;
+  let third: u64 = harness.send(VIEWER, "!up").await;
+  assert_eq!(harness.queued().await, 2);
+  harness.start();
+  harness.wait_until_idle().await;
+
+  let mut expected: Vec<KeyEvent> = taps(&[ZKey], 1);
+  expected.extend(taps(&[UpKey], 1));
+  assert_eq!(harness.keys(), expected);
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Status(first, "⏳"),
+    ChatEvent::Status(second, "⏳"),
+    ChatEvent::Status(third, "⏳"),
+    ChatEvent::Status(first, "🗑️"),
+    ChatEvent::Status(second, "✅"),
+    ChatEvent::Status(third, "✅")
+  ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inputs_are_held_while_the_game_is_unfocused() {
+  let harness: Harness = Harness::new(Options { focused: false, ..Options::default() });
+  let id: u64 = harness.send(VIEWER, "!a").await;
+  harness.start();
+  tokio::time::sleep(Duration::from_millis(100)).await;
+
+  assert!(harness.keys().is_empty());
+  assert_eq!(harness.queued().await, 1);
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Status(id, "⏳"),
+    ChatEvent::Said("The game lost focus, so inputs are on hold until it's back.".to_string())
+  ]);
+
+  harness.set_focused(true);
+  harness.wait_until_idle().await;
+  assert_eq!(harness.keys(), taps(&[XKey], 1));
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Said("The game has focus again, so inputs are back on.".to_string()),
+    ChatEvent::Status(id, "✅")
+  ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inputs_are_dropped_while_the_game_is_unfocused() {
+  let harness: Harness = Harness::new(Options {
+    focused: false,
+    focus_loss: FocusLossPolicy::Drop,
+    ..Options::default()
+  });
+  let id: u64 = harness.send(VIEWER, "!a").await;
+  harness.start();
+  harness.wait_until_idle().await;
+
+  assert!(harness.keys().is_empty());
+  assert!(harness.chat.events().contains(&ChatEvent::Status(id, "🗑️")));
+}
+
+// Held inputs would never go out, so the console gives up on them instead of
+// waiting forever for focus to come back.
+#[tokio::test(flavor = "multi_thread")]
+async fn console_scripts_finish_while_the_game_is_unfocused() {
+  let harness: Harness = Harness::new(Options { focused: false, ..Options::default() });
+  let script: String = harness.script("alice: !a\nbob: !b 2\n");
+  let mut console: BotConsole = BotConsole::new(Arc::clone(&harness.dispatcher), Some(script), "op".to_string());
+  tokio::time::timeout(Duration::from_secs(10), console.start()).await
+    .expect("The console never finished the script.")
+    .unwrap();
+
+  assert!(harness.keys().is_empty());
+  assert_eq!(harness.queued().await, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn admin_commands_are_only_for_admins() {
+  let harness: Harness = Harness::new(Options::default());
+  harness.send(VIEWER, "!a").await;
+  let refused: u64 = harness.send(VIEWER, "!clear").await;
+  assert_eq!(harness.queued().await, 1);
+  assert_eq!(harness.chat.events()[1], ChatEvent::Reacted(refused, "🚫".to_string()));
+
+  harness.send(ADMIN, "!clear").await;
+  assert_eq!(harness.queued().await, 0);
+  assert!(harness.chat.events().contains(&ChatEvent::Said("Cleared 1 queued input(s).".to_string())));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn command_names_are_normalized() {
+  let harness: Harness = Harness::new(Options::default());
+  harness.send(ADMIN, "!Pause!").await;
+  assert!(harness.dispatcher.is_paused());
+  harness.send(ADMIN, "!RESUME,").await;
+  assert!(!harness.dispatcher.is_paused());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queue_feedback_reacts_with_the_position() {
+  let harness: Harness = Harness::new(Options { queue_feedback: QueueFeedback::Reaction, ..Options::default() });
+  let first: u64 = harness.send(VIEWER, "!a").await;
+  let second: u64 = harness.send(VIEWER, "!b").await;
+
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Status(first, "⏳"),
+    ChatEvent::Reacted(first, "1️⃣".to_string()),
+    ChatEvent::Status(second, "⏳"),
+    ChatEvent::Reacted(second, "2️⃣".to_string())
+  ]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_commands_get_a_suggestion() {
+  let harness: Harness = Harness::new(Options::default());
+  let id: u64 = harness.send(VIEWER, "!upp").await;
+
+  assert_eq!(harness.chat.events(), vec![
+    ChatEvent::Status(id, "❌"),
+    ChatEvent::Replied(id, "Did you mean `up`?".to_string())
+  ]);
+}
\ No newline at end of file